@@ -0,0 +1,380 @@
+/*!
+
+Branching heuristics decide which literal the solver assigns next when propagation has nothing left
+to do. A heuristic implements `BranchingStrategy`. The solver notifies it of the events it may learn
+from, namely conflicts, assignments, unassignments on backtracking, and new variables, and asks it
+to `pick` at every decision.
+
+Two heuristics are built in, selected by `Config::branching_heuristic`:
+
+ * `Vsids`, Variable State Independent Decaying Sum. Variables involved in a conflict have their
+   activity bumped by an increment that grows geometrically, so recent conflicts weigh more.
+ * `Chb`, Conflict History-Based branching. A variable's activity is an exponential moving average of
+   a reward that is high for variables that recently took part in conflicts. See Liang et al.,
+   "Exponential Recency Weighted Average Branching Heuristic for SAT Solvers", AAAI 2016.
+
+Both pick the unassigned variable of highest activity and give it its saved phase.
+
+*/
+
+use crate::{
+  config::{BranchingHeuristic, Config},
+  variable_queue::VariableQueue,
+  BoolVariable,
+  LiftedBool,
+  Literal,
+  LiteralVector,
+  Solver,
+};
+
+/// Activities are rescaled when one exceeds this limit.
+const ACTIVITY_LIMIT: u32 = 1 << 24;
+
+/// Variables start with a random activity below this, the first VSIDS increment, to break the ties
+/// between them. A single bump outweighs it.
+pub(crate) const TIEBREAK_RANGE: u32 = 128;
+
+pub trait BranchingStrategy {
+  /// Chooses the next decision literal, or returns `None` if every decision variable is assigned.
+  fn pick(&mut self, solver: &Solver) -> Option<Literal>;
+
+  /// Called after each conflict with the learned clause and the variables resolved on while
+  /// deriving it.
+  fn on_conflict(&mut self, _solver: &Solver, _lemma: &LiteralVector, _involved: &[BoolVariable]) {}
+
+  /// Called whenever a literal is assigned, whether by a decision or by propagation.
+  fn on_assign(&mut self, _solver: &Solver, _literal: Literal) {}
+
+  /// Called for every variable unassigned by backtracking.
+  fn on_unassign(&mut self, _solver: &Solver, _v: BoolVariable) {}
+
+  /// Called when the solver creates the variable `v`.
+  fn on_new_variable(&mut self, _solver: &Solver, _v: BoolVariable) {}
+
+  /// Replaces the activities of the unassigned variables by their rank scaled by `activity_scale`,
+  /// keeping their relative order. Strategies without activities ignore this.
+  fn reorder(&mut self, _solver: &Solver, _activity_scale: u32) {}
+
+  /// Sets the activity of each variable `v` to `activities[v]`, to bias the search before it
+  /// starts. Strategies without activities ignore this.
+  fn seed_activities(&mut self, _solver: &Solver, _activities: &[u32]) {}
+}
+
+/// Creates the built-in strategy selected by `config.branching_heuristic`.
+pub(crate) fn from_config(config: &Config) -> Box<dyn BranchingStrategy> {
+  match config.branching_heuristic {
+    BranchingHeuristic::Vsids => Box::new(Vsids::new(config)),
+    BranchingHeuristic::Chb   => Box::new(Chb::new(config)),
+  }
+}
+
+/// Whether `v` may be decided on next.
+fn is_candidate(solver: &Solver, v: BoolVariable) -> bool {
+  solver.is_decision(v)
+    && !solver.eliminated[v]
+    && solver.value(Literal::positive(v)) == LiftedBool::Undefined
+}
+
+/// The activities and the case split queue ordered by them, shared by both built-in heuristics.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+struct ActivityQueue {
+  activity: Vec<u32>,
+  queue   : VariableQueue,
+}
+
+impl ActivityQueue {
+  fn add_variable(&mut self, solver: &Solver, v: BoolVariable) {
+    if self.activity.len() <= v {
+      self.activity.resize(v + 1, 0);
+    }
+    self.activity[v] = solver.tiebreak(v);
+    self.queue.insert(v, &self.activity);
+  }
+
+  fn pick(&mut self, solver: &Solver) -> Option<Literal> {
+    while let Some(v) = self.queue.pop_max(&self.activity) {
+      if is_candidate(solver, v) {
+        return Some(Literal::new(v, !solver.phase[v]));
+      }
+    }
+    None
+  }
+
+  fn set_activity(&mut self, v: BoolVariable, activity: u32) {
+    let increased    = activity > self.activity[v];
+    self.activity[v] = activity;
+    if increased {
+      self.queue.activity_increased(v, &self.activity);
+    } else {
+      self.queue.activity_decreased(v, &self.activity);
+    }
+  }
+
+  fn seed(&mut self, activities: &[u32]) {
+    for (v, &activity) in activities.iter().enumerate().take(self.activity.len()) {
+      self.activity[v] = activity;
+    }
+    self.queue.rebuild(&self.activity);
+  }
+
+  fn reorder(&mut self, solver: &Solver, activity_scale: u32) {
+    // todo: z3 samples the new order from a softmax over the activities with inverse temperature
+    //       `reorder_itau`. This ranks the variables deterministically instead.
+    let mut variables: Vec<BoolVariable> =
+      (0..self.activity.len())
+        .filter(|&v| !solver.eliminated[v] && solver.value(Literal::positive(v)) == LiftedBool::Undefined)
+        .collect();
+    variables.sort_by(|&u, &v| {
+      self.activity[v].cmp(&self.activity[u])
+          .then(solver.tiebreak(v).cmp(&solver.tiebreak(u)))
+          .then(u.cmp(&v))
+    });
+
+    let count = variables.len() as u32;
+    for (rank, &v) in variables.iter().enumerate() {
+      self.activity[v] = activity_scale.saturating_mul(count - rank as u32);
+      self.queue.insert(v, &self.activity);
+    }
+    self.queue.rebuild(&self.activity);
+  }
+}
+
+// region VSIDS
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Vsids {
+  activities    : ActivityQueue,
+  activity_inc  : u32,
+  variable_decay: u32, // The increment is multiplied by `variable_decay / 100` after every conflict
+}
+
+impl Vsids {
+  pub fn new(config: &Config) -> Self {
+    Vsids {
+      activities    : ActivityQueue::default(),
+      activity_inc  : 128,
+      variable_decay: config.variable_decay,
+    }
+  }
+
+  pub fn activity(&self, v: BoolVariable) -> u32 {
+    self.activities.activity[v]
+  }
+
+  fn bump(&mut self, v: BoolVariable) {
+    self.activities.activity[v] += self.activity_inc;
+    self.activities.queue.activity_increased(v, &self.activities.activity);
+
+    if self.activities.activity[v] > ACTIVITY_LIMIT {
+      for activity in self.activities.activity.iter_mut() {
+        *activity >>= 14;
+      }
+      self.activity_inc >>= 14;
+    }
+  }
+}
+
+impl BranchingStrategy for Vsids {
+  fn pick(&mut self, solver: &Solver) -> Option<Literal> {
+    self.activities.pick(solver)
+  }
+
+  fn on_conflict(&mut self, _solver: &Solver, _lemma: &LiteralVector, involved: &[BoolVariable]) {
+    for &v in involved {
+      self.bump(v);
+    }
+    self.activity_inc = (self.activity_inc as u64 * self.variable_decay as u64 / 100) as u32;
+  }
+
+  fn on_unassign(&mut self, _solver: &Solver, v: BoolVariable) {
+    self.activities.queue.insert(v, &self.activities.activity);
+  }
+
+  fn on_new_variable(&mut self, solver: &Solver, v: BoolVariable) {
+    self.activities.add_variable(solver, v);
+  }
+
+  fn reorder(&mut self, solver: &Solver, activity_scale: u32) {
+    self.activities.reorder(solver, activity_scale);
+    self.activity_inc = 128;
+  }
+
+  fn seed_activities(&mut self, _solver: &Solver, activities: &[u32]) {
+    self.activities.seed(activities);
+  }
+}
+
+// endregion VSIDS
+
+// region CHB
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Chb {
+  activities       : ActivityQueue,
+  last_conflict    : Vec<u64>, // The conflict count when the variable last took part in a conflict
+  last_propagation : Vec<u64>, // The conflict count when the variable was last assigned
+  participated     : Vec<u64>, // Conflicts the variable took part in since it was last assigned
+  canceled         : Vec<u64>, // The conflict count when the variable was last unassigned or decayed
+  anti_exploration : bool,
+  step_size        : f64,
+  step_size_dec    : f64,
+  step_size_min    : f64,
+  reward_multiplier: f64,
+  reward_offset    : f64,      // Scales the rewards, which are below 1, to integral activities
+}
+
+impl Chb {
+  pub fn new(config: &Config) -> Self {
+    Chb {
+      activities       : ActivityQueue::default(),
+      last_conflict    : Vec::new(),
+      last_propagation : Vec::new(),
+      participated     : Vec::new(),
+      canceled         : Vec::new(),
+      anti_exploration : config.anti_exploration,
+      step_size        : config.step_size_init,
+      step_size_dec    : config.step_size_dec,
+      step_size_min    : config.step_size_min,
+      reward_multiplier: config.reward_multiplier,
+      reward_offset    : config.reward_offset,
+    }
+  }
+
+  pub fn activity(&self, v: BoolVariable) -> u32 {
+    self.activities.activity[v]
+  }
+
+  /// Moves the activity of `v` toward the reward it earned while it was assigned.
+  fn update_activity(&mut self, solver: &Solver, v: BoolVariable) {
+    let conflicts = solver.solver_statistics().conflict as u64;
+    let age       = conflicts - self.last_propagation[v] + 1;
+    let reward    = self.reward_multiplier * self.participated[v] as f64 / age as f64;
+    let old       = self.activities.activity[v] as f64;
+    let new       = self.step_size * reward * self.reward_offset + (1.0 - self.step_size) * old;
+
+    self.activities.set_activity(v, new as u32);
+  }
+
+  /// Anti-exploration: before the variable of highest activity is picked, its activity decays by a
+  /// factor of 0.95 for every conflict since it was last unassigned, so that variables that have
+  /// sat out many conflicts do not win on stale rewards. Decaying may promote another variable,
+  /// which is then decayed in turn.
+  fn decay_dormant(&mut self, solver: &Solver) {
+    let conflicts = solver.solver_statistics().conflict as u64;
+    while let Some(v) = self.activities.queue.peek_max() {
+      let age = conflicts.saturating_sub(self.canceled[v]);
+      if age == 0 {
+        break;
+      }
+      let decayed      = self.activities.activity[v] as f64 * 0.95f64.powi(age.min(i32::MAX as u64) as i32);
+      self.canceled[v] = conflicts;
+      self.activities.set_activity(v, decayed as u32);
+    }
+  }
+}
+
+impl BranchingStrategy for Chb {
+  fn pick(&mut self, solver: &Solver) -> Option<Literal> {
+    if self.anti_exploration {
+      self.decay_dormant(solver);
+    }
+    self.activities.pick(solver)
+  }
+
+  fn on_conflict(&mut self, solver: &Solver, _lemma: &LiteralVector, involved: &[BoolVariable]) {
+    let conflicts = solver.solver_statistics().conflict as u64;
+    for &v in involved {
+      self.last_conflict[v]  = conflicts;
+      self.participated[v]  += 1;
+    }
+    self.step_size = (self.step_size - self.step_size_dec).max(self.step_size_min);
+  }
+
+  fn on_assign(&mut self, solver: &Solver, literal: Literal) {
+    let v = literal.var();
+    self.last_propagation[v] = solver.solver_statistics().conflict as u64;
+    self.participated[v]     = 0;
+  }
+
+  fn on_unassign(&mut self, solver: &Solver, v: BoolVariable) {
+    self.canceled[v] = solver.solver_statistics().conflict as u64;
+    self.update_activity(solver, v);
+    self.activities.queue.insert(v, &self.activities.activity);
+  }
+
+  fn on_new_variable(&mut self, solver: &Solver, v: BoolVariable) {
+    self.activities.add_variable(solver, v);
+    self.last_conflict.resize(self.activities.activity.len(), 0);
+    self.last_propagation.resize(self.activities.activity.len(), 0);
+    self.participated.resize(self.activities.activity.len(), 0);
+    self.canceled.resize(self.activities.activity.len(), 0);
+  }
+
+  fn reorder(&mut self, solver: &Solver, activity_scale: u32) {
+    self.activities.reorder(solver, activity_scale);
+  }
+
+  fn seed_activities(&mut self, _solver: &Solver, activities: &[u32]) {
+    self.activities.seed(activities);
+  }
+}
+
+// endregion CHB
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn vsids_reorder_follows_bumped_activities() {
+    let mut solver = Solver::default();
+    let variables: Vec<BoolVariable> = (0..4).map(|_| solver.mk_var(true, true)).collect();
+    let mut vsids = Vsids::new(solver.get_config());
+    for &v in variables.iter() {
+      vsids.on_new_variable(&solver, v);
+    }
+
+    vsids.on_conflict(&solver, &LiteralVector::new(), &[variables[2], variables[3]]);
+    vsids.on_conflict(&solver, &LiteralVector::new(), &[variables[2]]);
+    vsids.reorder(&solver, 100);
+
+    assert_eq!(vsids.activity(variables[2]), 400);
+    assert_eq!(vsids.activity(variables[3]), 300);
+    assert_eq!(vsids.pick(&solver).map(|l| l.var()), Some(variables[2]));
+    assert_eq!(vsids.pick(&solver).map(|l| l.var()), Some(variables[3]));
+    assert_eq!(vsids.pick(&solver).map(|l| l.var()), Some(variables[0]));
+  }
+
+  #[test]
+  fn chb_anti_exploration_decays_dormant_variables() {
+    use crate::status::Status;
+
+    // Refuting all four clauses over x and y takes at least one conflict.
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    for clause in [vec![x, y], vec![x, !y], vec![!x, y], vec![!x, !y]] {
+      solver.mk_clause_core(&clause, Status::input());
+    }
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    let conflicts = solver.solver_statistics().conflict as u64;
+    assert!(conflicts > 0);
+
+    let mut config = solver.get_config().clone();
+    config.anti_exploration = true;
+    let mut chb = Chb::new(&config);
+    chb.on_new_variable(&solver, x.var());
+    chb.on_new_variable(&solver, y.var());
+
+    // `x` has the higher activity but has been dormant since the start; `y` was just unassigned.
+    chb.activities.set_activity(x.var(), 1000);
+    chb.activities.set_activity(y.var(), 960);
+    chb.canceled[y.var()] = conflicts;
+
+    chb.decay_dormant(&solver);
+    assert!(chb.activity(x.var()) < chb.activity(y.var()));
+    assert_eq!(chb.activity(y.var()), 960);
+    assert_eq!(chb.activities.queue.peek_max(), Some(y.var()));
+  }
+}
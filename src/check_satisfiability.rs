@@ -14,7 +14,7 @@
 
 use std::rc::Rc;
 
-use crate::{LiftedBool, Model, Statistics};
+use crate::{LiftedBool, LiteralVector, Model, Statistics};
 use crate::symbol_table::SymbolData;
 use crate::missing_types::*;
 
@@ -58,6 +58,35 @@ pub trait SatisfiabilityCheckResult {
   fn collect_timer_stats(&self, statistics: &mut Statistics);
 }
 
+/// The outcome of `Solver::check_rich`. Unlike the bare `LiftedBool` returned by `Solver::check`, it
+/// carries the model, the core, or the reason the result is unknown.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CheckResult {
+  Satisfiable(Model),
+  /// The core is the subset of the assumptions that is inconsistent with the clauses.
+  Unsatisfiable(LiteralVector),
+  /// Holds the reason the search gave up.
+  Unknown(String),
+}
+
+impl CheckResult {
+  pub fn status(&self) -> LiftedBool {
+    match self {
+      CheckResult::Satisfiable(_)   => LiftedBool::True,
+      CheckResult::Unsatisfiable(_) => LiftedBool::False,
+      CheckResult::Unknown(_)       => LiftedBool::Undefined,
+    }
+  }
+
+  /// The core as DIMACS integers, if the result is unsatisfiable.
+  pub fn dimacs_core(&self) -> Option<Vec<i32>> {
+    match self {
+      CheckResult::Unsatisfiable(core) => Some(core.iter().map(|literal| literal.to_dimacs()).collect()),
+      _                                => None,
+    }
+  }
+}
+
 /*
   class scoped_solver_time {
       check_sat_result& c;
@@ -106,7 +135,7 @@ impl SatisfiabilityCheckResult for SimpleSatisfiabilityCheckResult {
   }
 
   fn collect_statistics(&self, statistics: &mut Statistics) {
-    statistics.update_with(self.statistics);
+    statistics.extend(&self.statistics);
   }
 
   fn get_unsat_core(&self, ev: &mut ExpressionVector) {
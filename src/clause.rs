@@ -6,22 +6,29 @@ $x_1 \lor \overline{x}_2 \lor \overline{x}_3 \lor x_4$.
 
  */
 
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 use std::ops::Index;
 use itertools::Itertools;
 
+
 use crate::{
   BoolVariable,
   Literal,
   LiteralVector,
   VariableApproximateSet,
-  data_structures::ApproximateSet
+  config::GcStrategy,
+  data_structures::ApproximateSet,
+  Model,
 };
 
 pub type ClauseOffset = usize;
 pub type ClauseVector = Vec<Clause>;
 pub type ClauseWrapperVector = Vec<ClauseWrapper>;
 
+/// When a clause activity exceeds this value, all clause activities are rescaled by its inverse.
+pub const CLAUSE_ACTIVITY_LIMIT: f64 = 1e20;
+
 
 /// The primary clause representation. `Clause`'s are garbage collected.
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
@@ -36,12 +43,14 @@ pub struct Clause {
   inact_rounds: u32,
   glue        : u32,
   psm         : u32, // Transient field used during gc
+  activity    : f64, // Bumped when the clause participates in a conflict
 
   is_strengthened: bool,
   is_removed     : bool,
   is_learned     : bool,
   is_used        : bool,
   is_frozen      : bool,
+  is_core        : bool, // Learned clauses in the core tier are never garbage collected
   reinit_stack   : bool,
 }
 
@@ -56,11 +65,13 @@ impl Clause {
   pub fn inact_rounds(&self)    -> u32  { self.inact_rounds    }
   pub fn glue(&self)            -> u32  { self.glue            }
   pub fn psm(&self)             -> u32  { self.psm             }
+  pub fn activity(&self)        -> f64  { self.activity        }
   pub fn is_strengthened(&self) -> bool { self.is_strengthened }
   pub fn is_removed(&self)      -> bool { self.is_removed      }
   pub fn is_learned(&self)      -> bool { self.is_learned      }
   pub fn is_used(&self)         -> bool { self.is_used         }
   pub fn is_frozen(&self)       -> bool { self.is_frozen       }
+  pub fn is_core(&self)         -> bool { self.is_core         }
   pub fn reinit_stack(&self)    -> bool { self.reinit_stack    }
 
   // pub fn set_literals(&mut self     , literals     : LiteralVector          )  { self.literals = literals;}
@@ -70,6 +81,7 @@ impl Clause {
   pub fn set_removed(&mut self      , is_removed   :  bool ) { self.is_removed   = is_removed;   }
   pub fn set_used(&mut self         , is_used      :  bool ) { self.is_used      = is_used;      }
   pub fn set_reinit_stack(&mut self , reinit_stack :  bool ) { self.reinit_stack = reinit_stack; }
+  pub fn set_core(&mut self         , is_core      :  bool ) { self.is_core      = is_core;      }
 
 
   // Setters needing special treatment
@@ -118,6 +130,18 @@ impl Clause {
     self.inact_rounds = 0;
   }
 
+  /// Increases the activity of the clause by `increment`. Returns `true` if the activity has grown
+  /// large enough that all clause activities should be rescaled.
+  pub fn bump_activity(&mut self, increment: f64) -> bool {
+    self.activity += increment;
+    self.activity > CLAUSE_ACTIVITY_LIMIT
+  }
+
+  /// Multiplies the activity by `factor`. Used to rescale all activities when one overflows.
+  pub fn scale_activity(&mut self, factor: f64) {
+    self.activity *= factor;
+  }
+
   pub fn set_strengthened(&mut self, is_strengthened: bool) {
     if is_strengthened {
       self.is_strengthened = true;
@@ -156,6 +180,17 @@ impl Clause {
     self.literals.contains(&literal)
   }
 
+  /// Whether some literal of the clause is true in `model`. Variables the model leaves undefined or
+  /// does not cover satisfy nothing.
+  pub fn satisfied_by(&self, model: &Model) -> bool {
+    model.satisfies_clause(self)
+  }
+
+  /// Exchanges the literals at positions `i` and `j`, as when moving a watch.
+  pub fn swap(&mut self, i: usize, j: usize) {
+    self.literals.swap(i, j);
+  }
+
   /// Remove every instance of `literal`. This operation is done in-place.
   pub fn eliminate(&mut self, literal: Literal) {
     let initial_size = self.literals.len();
@@ -173,13 +208,22 @@ impl Clause {
 
   // endregion Methods forwarded to `self.literals`
 
+  /// An estimate of the number of bytes the clause occupies, including its literals.
+  pub fn memory_size(&self) -> usize {
+    std::mem::size_of::<Clause>() + self.literals.capacity() * std::mem::size_of::<Literal>()
+  }
+
   pub fn update_approx(&mut self, values: &[Literal]) {
     self.approx = VariableApproximateSet::with_values(values.iter().map(|a| a.var()).collect())
   }
 
-  fn new(id: u32, literals: LiteralVector, learned: bool) -> Self {
+  pub(crate) fn new(id: u32, literals: LiteralVector, learned: bool) -> Self {
+    let size = literals.len() as u32;
     Self {
       id,
+      size,
+      capacity  : size,
+      approx    : VariableApproximateSet::with_values(literals.iter().map(|a| a.var()).collect()),
       literals,
       is_learned: learned,
       ..Clause::default()
@@ -205,15 +249,17 @@ impl Default for Clause {
       id             :  0,
       size           :  0,
       capacity       :  0,
-      inact_rounds   :  8,
-      glue           :  8,
-      psm            :  8,
-      is_strengthened:  true,
-      is_removed     :  true,
-      is_learned     :  true,
-      is_used        :  true,
-      is_frozen      :  true,
-      reinit_stack   :  true,
+      inact_rounds   :  0,
+      glue           :  255,
+      psm            :  255,
+      activity       :  0.0,
+      is_strengthened:  false,
+      is_removed     :  false,
+      is_learned     :  false,
+      is_used        :  false,
+      is_frozen      :  false,
+      is_core        :  false,
+      reinit_stack   :  false,
     }
   }
 }
@@ -238,19 +284,87 @@ impl Index<u32> for Clause {
   }
 }
 
+/// Formats literals in DIMACS form, for example `{1 -2 3}`.
+fn format_dimacs_literals(literals: &[Literal]) -> String {
+  format!("{{{}}}", literals.iter().map(|literal| literal.to_dimacs()).join(" "))
+}
+
+/// Renders the clause as `{1 -2 3}`. Debug builds append `x` if the clause is removed, `+` if it is
+/// strengthened, and `*` and its glue if it is learned.
 impl Display for Clause {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    write!(
-      f,
-      "({}){}{}{}",
-      self.literals.format(" "),                  // False error: `Format` does implement `Display`.
-      if self.is_removed() { "x" } else { "" },
-      if self.is_strengthened() { "+" } else { "" },
-      if self.is_learned() { "*" } else { "" },
-    )
+    // `size` may lag behind `literals` while the clause is being edited. Don't panic on it.
+    let size = usize::min(self.size as usize, self.literals.len());
+    write!(f, "{}", format_dimacs_literals(&self.literals[..size]))?;
+
+    if cfg!(debug_assertions) {
+      write!(
+        f,
+        "{}{}",
+        if self.is_removed() { "x" } else { "" },
+        if self.is_strengthened() { "+" } else { "" },
+      )?;
+      if self.is_learned() {
+        write!(f, "* glue: {}", self.glue)?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+// region GC orderings
+
+// Each ordering puts the clauses most worth keeping first, so that garbage collection can delete
+// from the back of the sorted vector.
+
+/// Lower glue first.
+pub fn glue_lt(c1: &Clause, c2: &Clause) -> Ordering {
+  c1.glue.cmp(&c2.glue)
+}
+
+/// Lower psm first.
+pub fn psm_lt(c1: &Clause, c2: &Clause) -> Ordering {
+  c1.psm.cmp(&c2.psm)
+}
+
+/// Lower glue first, ties broken by lower psm.
+pub fn glue_psm_lt(c1: &Clause, c2: &Clause) -> Ordering {
+  glue_lt(c1, c2).then_with(|| psm_lt(c1, c2))
+}
+
+/// Lower psm first, ties broken by lower glue.
+pub fn psm_glue_lt(c1: &Clause, c2: &Clause) -> Ordering {
+  psm_lt(c1, c2).then_with(|| glue_lt(c1, c2))
+}
+
+/// Higher activity first, ties broken by lower glue.
+pub fn activity_lt(c1: &Clause, c2: &Clause) -> Ordering {
+  c2.activity
+    .partial_cmp(&c1.activity)
+    .unwrap_or(Ordering::Equal)
+    .then_with(|| glue_lt(c1, c2))
+}
+
+/// The ordering `strategy` uses to rank clauses, most valuable first.
+pub fn gc_ordering(strategy: GcStrategy) -> fn(&Clause, &Clause) -> Ordering {
+  match strategy {
+    GcStrategy::Glue     => glue_lt,
+    GcStrategy::Psm      => psm_lt,
+    GcStrategy::GluePsm  => glue_psm_lt,
+    GcStrategy::PsmGlue  => psm_glue_lt,
+    GcStrategy::DynPsm   => psm_glue_lt,
+    GcStrategy::Activity => activity_lt,
   }
 }
 
+/// Sorts `clauses` so that the clauses `strategy` considers most valuable come first.
+pub fn sort_for_gc(clauses: &mut [Clause], strategy: GcStrategy) {
+  clauses.sort_by(gc_ordering(strategy));
+}
+
+// endregion GC orderings
+
 
 /// A wrapper type for `Clause` that provides a much smaller representation
 /// for binary clauses. Only a subset of the `ClauseCore` API is provided.
@@ -319,6 +433,26 @@ impl ClauseWrapper {
 }
 
 
+impl Display for ClauseWrapper {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+
+      ClauseWrapper::Binary { literal1, literal2, is_learned } => {
+        write!(f, "{}", format_dimacs_literals(&[*literal1, *literal2]))?;
+        if cfg!(debug_assertions) && *is_learned {
+          write!(f, "*")?;
+        }
+        Ok(())
+      },
+
+      ClauseWrapper::Nonbinary(c) => {
+        c.fmt(f)
+      }
+
+    }
+  }
+}
+
 impl Index<usize> for ClauseWrapper {
   type Output = Literal;
 
@@ -347,8 +481,50 @@ impl Index<usize> for ClauseWrapper {
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  #[test]
+  fn display_uses_dimacs_literals() {
+    let clause = Clause::new(0, vec![Literal::new(0, false), Literal::new(1, true), Literal::new(2, false)], false);
+    assert_eq!(clause.to_string(), "{1 -2 3}");
+
+    let binary = ClauseWrapper::Binary {
+      literal1  : Literal::new(3, true),
+      literal2  : Literal::new(4, false),
+      is_learned: false
+    };
+    assert_eq!(binary.to_string(), "{-4 5}");
+    assert_eq!(ClauseWrapper::Nonbinary(Box::new(clause)).to_string(), "{1 -2 3}");
+  }
+
+  #[test]
+  fn display_tolerates_stale_size() {
+    let mut clause = Clause::new(0, vec![Literal::new(0, false), Literal::new(1, true)], false);
+    clause.set_size(5);
+    assert!(clause.to_string().starts_with("{1 -2}"));
+  }
+
+  #[test]
+  fn bumped_clause_survives_activity_gc() {
+    let mut untouched = Clause::new(0, vec![Literal(0), Literal(2), Literal(4)], true);
+    let mut bumped    = Clause::new(1, vec![Literal(1), Literal(3), Literal(5)], true);
+    untouched.set_glue(3);
+    bumped.set_glue(3);
+    for _ in 0..5 {
+      bumped.bump_activity(1.0);
+    }
+
+    let mut learned = vec![untouched, bumped];
+    sort_for_gc(&mut learned, GcStrategy::Activity);
+    // Delete the worse half.
+    learned.truncate(learned.len() / 2);
+
+    assert_eq!(learned.len(), 1);
+    assert_eq!(learned[0].id(), 1);
+  }
 }
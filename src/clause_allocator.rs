@@ -0,0 +1,147 @@
+/*!
+
+A `ClauseAllocator` owns the storage for all nonbinary clauses. Clauses are stored contiguously and
+referred to everywhere else, in particular from the watch lists, by their `ClauseOffset` into this
+arena.
+
+Freeing a clause only marks its slot as free. Offsets of the remaining clauses stay valid until
+`collect_garbage` compacts the arena, which returns a map from old offsets to new offsets so that
+the caller can fix up every place an offset is stored.
+
+*/
+
+use crate::{
+  clause::{Clause, ClauseOffset},
+  LiteralVector
+};
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct ClauseAllocator {
+  clauses   : Vec<Option<Clause>>, // `None` marks a freed slot
+  free_count: usize,
+  id_gen    : u32,
+}
+
+impl ClauseAllocator {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Allocates a new clause with the given literals and returns its offset.
+  pub fn allocate(&mut self, literals: LiteralVector, learned: bool) -> ClauseOffset {
+    let clause = Clause::new(self.id_gen, literals, learned);
+    self.id_gen += 1;
+
+    self.clauses.push(Some(clause));
+    self.clauses.len() - 1
+  }
+
+  /// Panics if `offset` does not refer to a live clause.
+  pub fn get(&self, offset: ClauseOffset) -> &Clause {
+    self.clauses[offset].as_ref().expect("clause offset refers to a freed clause")
+  }
+
+  /// Panics if `offset` does not refer to a live clause.
+  pub fn get_mut(&mut self, offset: ClauseOffset) -> &mut Clause {
+    self.clauses[offset].as_mut().expect("clause offset refers to a freed clause")
+  }
+
+  pub fn is_live(&self, offset: ClauseOffset) -> bool {
+    matches!(self.clauses.get(offset), Some(Some(_)))
+  }
+
+  /// Releases the clause at `offset`. Its slot is reclaimed by the next `collect_garbage`.
+  pub fn free(&mut self, offset: ClauseOffset) {
+    sassert!(self.is_live(offset));
+
+    if self.clauses[offset].take().is_some() {
+      self.free_count += 1;
+    }
+  }
+
+  /// Compacts the arena, moving every live clause down over the freed slots. Returns a vector
+  /// indexed by old offset that gives each live clause's new offset, or `None` for freed slots.
+  pub fn collect_garbage(&mut self) -> Vec<Option<ClauseOffset>> {
+    let mut remap = Vec::with_capacity(self.clauses.len());
+    let mut next  = 0;
+
+    for offset in 0..self.clauses.len() {
+      match self.clauses[offset].take() {
+        Some(clause) => {
+          // `next <= offset`, so this never overwrites a live clause.
+          self.clauses[next] = Some(clause);
+          remap.push(Some(next));
+          next += 1;
+        }
+        None => remap.push(None),
+      }
+    }
+
+    self.clauses.truncate(next);
+    self.free_count = 0;
+    remap
+  }
+
+  /// The number of live clauses.
+  pub fn len(&self) -> usize {
+    self.clauses.len() - self.free_count
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// The number of slots that `collect_garbage` would reclaim.
+  pub fn free_count(&self) -> usize {
+    self.free_count
+  }
+
+  /// Iterates over the live clauses together with their offsets.
+  pub fn iter(&self) -> impl Iterator<Item = (ClauseOffset, &Clause)> {
+    self.clauses
+        .iter()
+        .enumerate()
+        .filter_map(|(offset, slot)| slot.as_ref().map(|clause| (offset, clause)))
+  }
+
+  /// An estimate of the number of bytes used by the arena, including the freed slots.
+  pub fn memory_size(&self) -> usize {
+    self.clauses.capacity() * std::mem::size_of::<Option<Clause>>()
+      + self.iter()
+            .map(|(_, clause)| clause.literals().capacity() * std::mem::size_of::<crate::Literal>())
+            .sum::<usize>()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Literal;
+
+  #[test]
+  fn surviving_offsets_resolve_after_compaction() {
+    let mut allocator = ClauseAllocator::new();
+    let clauses: Vec<LiteralVector> = (0..4).map(
+      |i| vec![Literal::new(i, false), Literal::new(i + 1, true), Literal::new(i + 2, false)]
+    ).collect();
+    let offsets: Vec<ClauseOffset> = clauses.iter()
+                                            .map(|literals| allocator.allocate(literals.clone(), false))
+                                            .collect();
+
+    allocator.free(offsets[1]);
+    assert!(!allocator.is_live(offsets[1]));
+    assert_eq!(allocator.len(), 3);
+    assert_eq!(allocator.free_count(), 1);
+
+    let remap = allocator.collect_garbage();
+    assert_eq!(remap[offsets[1]], None);
+    assert_eq!(allocator.free_count(), 0);
+
+    for i in [0, 2, 3] {
+      let new_offset = remap[offsets[i]].unwrap();
+      assert_eq!(allocator.get(new_offset).literals(), &clauses[i]);
+      assert_eq!(allocator.get(new_offset).id(), i as u32);
+    }
+  }
+}
@@ -4,7 +4,7 @@
 
 */
 
-use std::rc::Rc;
+use std::{convert::TryFrom, rc::Rc};
 
 use crate::{
   symbol_table::SymbolData,
@@ -12,7 +12,7 @@ use crate::{
 };
 
 use super::local_search::LocalSearchMode;
-use crate::parameters::ParametersRef;
+use crate::parameters::{ParameterValue, ParametersRef};
 
 // region Enums used in `Config`
 
@@ -40,7 +40,8 @@ pub enum GcStrategy {
   Psm,
   Glue,
   GluePsm,
-  PsmGlue
+  PsmGlue,
+  Activity
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
@@ -83,29 +84,34 @@ pub enum CutoffType {
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 pub struct Config<'s> {
-  max_memory                : u64,
+  pub(crate) max_memory     : u64,             // in megabytes
   phase                     : PhaseSelection,
   search_sat_conflicts      : u32,
   search_unsat_conflicts    : u32,
-  pub phase_sticky          : bool,
-  rephase_base              : u32,
-  reorder_base              : u32,
-  reorder_itau              : f64,
-  reorder_activity_scale    : u32,
+  pub phase_sticky          : bool,   // Save phases aggressively, and start local search from them
+  pub(crate) rephase_base   : u32,
+
+  pub(crate) reorder_base          : u32,
+  reorder_itau                     : f64,
+  pub(crate) reorder_activity_scale: u32,
+
   propagate_prefetch        : bool,
-  restart                   : RestartStrategy,
+  pub(crate) restart        : RestartStrategy,
   restart_fast              : bool,
-  restart_initial           : u32,
-  restart_factor            : f64,             // for geometric case
-  restart_margin            : f64,             // for EMA
-  restart_max               : u32,
-  activity_scale            : u32,
+  pub(crate) restart_initial: u32,
+  pub(crate) restart_factor : f64,             // for geometric case
+  pub(crate) restart_margin : f64,             // for EMA
+  pub(crate) restart_max    : u32,
+  pub random_restart_freq   : f64,             // Probability of an extra restart after a conflict
+  pub(crate) activity_scale : u32,
+  pub init_activity_occurrences: bool,         // Seed activities from occurrence counts before search
   fast_glue_avg             : f64,
   slow_glue_avg             : f64,
   inprocess_max             : u32,
   inprocess_out             : SymbolData<'s>,
   random_freq               : f64,
   pub random_seed           : u32,
+  pub tiebreak_seed         : u32,   // Seeds only the tie-breaking between equally active variables; 0 breaks ties by index
   burst_search              : u32,
   enable_pre_simplify       : bool,
   max_conflicts             : u32,
@@ -118,6 +124,12 @@ pub struct Config<'s> {
   pub local_search_mode     : LocalSearchMode,
   pub local_search_dbg_flips: bool,
 
+  // Parallel solvers share a learned clause if it has at most `share_max_size` literals and glue at
+  // most `share_max_glue`, or if its glue is at most `share_always_glue` regardless of its size.
+  pub share_max_size   : u32,
+  pub share_max_glue   : u32,
+  pub share_always_glue: u32,
+
   binspr          : bool,
   cut_simplify    : bool,
   cut_delay       : u32,
@@ -147,26 +159,30 @@ pub struct Config<'s> {
   lookahead_delta_fraction       : f64,
   lookahead_use_learned          : bool,
 
-  incremental   : bool,
-  next_simplify1: u32,
-  simplify_mult2: f64,
-  simplify_max  : u32,
-  simplify_delay: u32,
-  variable_decay: u32,
-
-  gc_strategy   : GcStrategy,
-  gc_initial    : u32,
-  gc_increment  : u32,
-  gc_small_lbd  : u32,
-  gc_k          : u32,
-  gc_burst      : bool,
-  gc_defrag     : bool,
-  force_cleanup : bool,
+  incremental              : bool,
+  pub(crate) next_simplify1: u32,
+  pub(crate) simplify_mult2: f64,
+  pub(crate) simplify_max  : u32,
+  simplify_delay           : u32,
+  pub(crate) variable_decay: u32,
+
+  pub(crate) gc_strategy      : GcStrategy,
+  pub(crate) gc_activity_decay: f64,         // Decay factor for learned clause activity
+  gc_initial                  : u32,
+  pub(crate) gc_increment     : u32,
+  pub(crate) gc_small_lbd     : u32,         // Lemmas with at most this glue join the core tier
+  pub no_gc                   : bool,        // Keep every learned clause; the memory limit still applies
+  pub gc_glue_factor          : f64,         // Spare lemmas with glue at most this times the slow glue average; 0 disables
+  gc_k                        : u32,
+  gc_burst                    : bool,
+  gc_defrag                   : bool,
+  force_cleanup               : bool,
 
   // backtracking
   backtrack_scopes        : u32,
   backtrack_init_conflicts: u32,
-  minimize_lemmas         : bool,
+  pub(crate) minimize_lemmas: bool,
+  pub minimize_lemmas_binary: bool,  // Also drop lemma literals by resolving with binary clauses
   dyn_sub_res             : bool,
   core_minimize           : bool,
   core_minimize_partial   : bool,
@@ -180,17 +196,17 @@ pub struct Config<'s> {
   drat_activity   : bool,
   card_solver     : bool,
   xor_solver      : bool,
-  pb_resolve      : PbResolve,     // Pseudo-boolean Resolve
+  pub(crate) pb_resolve: PbResolve, // Pseudo-boolean Resolve
   pb_lemma_format : PbLemmaFormat, // Pseudo-boolean Resolve
 
   // branching heuristic settings
-  branching_heuristic: BranchingHeuristic,
-  anti_exploration   : bool,
-  step_size_init     : f64,
-  step_size_dec      : f64,
-  step_size_min      : f64,
-  reward_multiplier  : f64,
-  reward_offset      : f64,
+  pub(crate) branching_heuristic: BranchingHeuristic,
+  pub(crate) anti_exploration   : bool,
+  pub(crate) step_size_init     : f64,
+  pub(crate) step_size_dec      : f64,
+  pub(crate) step_size_min      : f64,
+  pub(crate) reward_multiplier  : f64,
+  pub(crate) reward_offset      : f64,
 
   // Simplifier configurations used outside of `SatSimplifier`
   elim_vars: bool,
@@ -199,10 +215,154 @@ pub struct Config<'s> {
 
 impl<'s> Config<'s>{
 
-  pub fn new(parameters: ParametersRef){
-    Self{
+  /// Builds the configuration from the `sat` module `parameters`. Parameters that are absent, have
+  /// the wrong type, or name an unknown strategy keep the value of `Config::default`.
+  pub fn new(parameters: &ParametersRef<'s>) -> Self {
+    let parameters = parameters.borrow();
+    let mut config = Self::default();
+
+    let uint = |name: &str, value: &mut u32| {
+      if let Some(ParameterValue::UnsignedInteger(number)) = parameters.get_value(name) {
+        *value = u32::try_from(number).unwrap_or(u32::MAX);
+      }
+    };
+    let double = |name: &str, value: &mut f64| {
+      if let Some(ParameterValue::Double(number)) = parameters.get_value(name) {
+        *value = number;
+      }
+    };
+    let boolean = |name: &str, value: &mut bool| {
+      if let Some(ParameterValue::Bool(flag)) = parameters.get_value(name) {
+        *value = flag;
+      }
+    };
+    let symbol = |name: &str| {
+      match parameters.get_value(name) {
+        Some(ParameterValue::Symbol(text)) => Some(text.to_string()),
+        _                                  => None,
+      }
+    };
+
+    if let Some(ParameterValue::UnsignedInteger(megabytes)) = parameters.get_value("max_memory") {
+      config.max_memory = megabytes;
+    }
+    if let Some(phase) = symbol("phase") {
+      config.phase = match phase.as_str() {
+        "always_false"  => PhaseSelection::AlwaysFalse,
+        "always_true"   => PhaseSelection::AlwaysTrue,
+        "basic_caching" => PhaseSelection::BasicCaching,
+        "caching"       => PhaseSelection::SATCaching,
+        "frozen"        => PhaseSelection::Frozen,
+        "random"        => PhaseSelection::Random,
+        _               => config.phase,
+      };
+    }
+    boolean("phase.sticky", &mut config.phase_sticky);
+    uint("search.sat.conflicts", &mut config.search_sat_conflicts);
+    uint("search.unsat.conflicts", &mut config.search_unsat_conflicts);
+    uint("rephase.base", &mut config.rephase_base);
+    uint("reorder.base", &mut config.reorder_base);
+    double("reorder.itau", &mut config.reorder_itau);
+    uint("reorder.activity_scale", &mut config.reorder_activity_scale);
+    boolean("propagate.prefetch", &mut config.propagate_prefetch);
+
+    if let Some(restart) = symbol("restart") {
+      config.restart = match restart.as_str() {
+        "static"    => RestartStrategy::Static,
+        "luby"      => RestartStrategy::Luby,
+        "ema"       => RestartStrategy::Ema,
+        "geometric" => RestartStrategy::Geometric,
+        _           => config.restart,
+      };
+    }
+    uint("restart.initial", &mut config.restart_initial);
+    uint("restart.max", &mut config.restart_max);
+    boolean("restart.fast", &mut config.restart_fast);
+    double("restart.factor", &mut config.restart_factor);
+    double("restart.margin", &mut config.restart_margin);
+    double("restart.emafastglue", &mut config.fast_glue_avg);
+    double("restart.emaslowglue", &mut config.slow_glue_avg);
+    uint("variable_decay", &mut config.variable_decay);
+    uint("inprocess.max", &mut config.inprocess_max);
+
+    if let Some(heuristic) = symbol("branching.heuristic") {
+      config.branching_heuristic = match heuristic.as_str() {
+        "vsids" => BranchingHeuristic::Vsids,
+        "chb"   => BranchingHeuristic::Chb,
+        _       => config.branching_heuristic,
+      };
+    }
+    boolean("branching.anti_exploration", &mut config.anti_exploration);
+    double("random_freq", &mut config.random_freq);
+    uint("random_seed", &mut config.random_seed);
+    uint("burst_search", &mut config.burst_search);
+    boolean("enable_pre_simplify", &mut config.enable_pre_simplify);
+    uint("max_conflicts", &mut config.max_conflicts);
+
+    if let Some(strategy) = symbol("gc") {
+      config.gc_strategy = match strategy.as_str() {
+        "psm"      => GcStrategy::Psm,
+        "glue"     => GcStrategy::Glue,
+        "glue_psm" => GcStrategy::GluePsm,
+        "psm_glue" => GcStrategy::PsmGlue,
+        "dyn_psm"  => GcStrategy::DynPsm,
+        "activity" => GcStrategy::Activity,
+        _          => config.gc_strategy,
+      };
+    }
+    double("gc.activity_decay", &mut config.gc_activity_decay);
+    uint("gc.initial", &mut config.gc_initial);
+    uint("gc.increment", &mut config.gc_increment);
+    uint("gc.small_lbd", &mut config.gc_small_lbd);
+    uint("gc.k", &mut config.gc_k);
+    boolean("gc.burst", &mut config.gc_burst);
+    boolean("gc.defrag", &mut config.gc_defrag);
+    uint("simplify.delay", &mut config.simplify_delay);
+    boolean("force_cleanup", &mut config.force_cleanup);
+    boolean("minimize_lemmas", &mut config.minimize_lemmas);
+    boolean("dyn_sub_res", &mut config.dyn_sub_res);
+    boolean("core.minimize", &mut config.core_minimize);
+    boolean("core.minimize_partial", &mut config.core_minimize_partial);
+    uint("backtrack.scopes", &mut config.backtrack_scopes);
+    uint("backtrack.conflicts", &mut config.backtrack_init_conflicts);
+    uint("threads", &mut config.num_threads);
 
+    boolean("drat.binary", &mut config.drat_binary);
+    boolean("drat.check_unsat", &mut config.drat_check_unsat);
+    boolean("drat.check_sat", &mut config.drat_check_sat);
+    boolean("drat.activity", &mut config.drat_activity);
+    boolean("cardinality.solver", &mut config.card_solver);
+    if let Some(resolve) = symbol("pb.resolve") {
+      config.pb_resolve = match resolve.as_str() {
+        "cardinality" => PbResolve::Cardinality,
+        "rounding"    => PbResolve::Rounding,
+        _             => config.pb_resolve,
+      };
     }
+    if let Some(format) = symbol("pb.lemma_format") {
+      config.pb_lemma_format = match format.as_str() {
+        "cardinality" => PbLemmaFormat::Cardinality,
+        "pb"          => PbLemmaFormat::Pb,
+        _             => config.pb_lemma_format,
+      };
+    }
+
+    boolean("ddfw_search", &mut config.ddfw_search);
+    uint("ddfw.threads", &mut config.ddfw_threads);
+    boolean("prob_search", &mut config.prob_search);
+    boolean("local_search", &mut config.local_search);
+    uint("local_search_threads", &mut config.local_search_threads);
+    if let Some(mode) = symbol("local_search_mode") {
+      config.local_search_mode = match mode.as_str() {
+        "gsat" => LocalSearchMode::GSAT,
+        "wsat" => LocalSearchMode::WSAT,
+        _      => config.local_search_mode,
+      };
+    }
+    boolean("local_search_dbg_flips", &mut config.local_search_dbg_flips);
+    boolean("binspr", &mut config.binspr);
+
+    config
   }
 
   pub fn update_parameters(parameters: ParametersRef){
@@ -214,6 +374,132 @@ impl<'s> Config<'s>{
   }
 }
 
+impl<'s> Default for Config<'s> {
+  /// The defaults of Z3's `sat` module parameters.
+  fn default() -> Self {
+    Config {
+      max_memory            : u32::MAX as u64,
+      phase                 : PhaseSelection::BasicCaching,
+      search_sat_conflicts  : 400,
+      search_unsat_conflicts: 400,
+      phase_sticky          : true,
+      rephase_base          : 1000,
+      reorder_base          : u32::MAX,
+      reorder_itau          : 4.0,
+      reorder_activity_scale: 100,
+      propagate_prefetch    : true,
+      restart               : RestartStrategy::Ema,
+      restart_fast          : true,
+      restart_initial       : 2,
+      restart_factor        : 1.5,
+      restart_margin        : 1.1,
+      restart_max           : u32::MAX,
+      random_restart_freq   : 0.0,
+      activity_scale        : 100,
+      init_activity_occurrences: false,
+      fast_glue_avg         : 3e-2,
+      slow_glue_avg         : 1e-5,
+      inprocess_max         : u32::MAX,
+      inprocess_out         : SymbolData::Null,
+      random_freq           : 0.01,
+      random_seed           : 0,
+      tiebreak_seed         : 0,
+      burst_search          : 100,
+      enable_pre_simplify   : false,
+      max_conflicts         : u32::MAX,
+      num_threads           : 1,
+      ddfw_search           : false,
+      ddfw_threads          : 0,
+      prob_search           : false,
+      local_search_threads  : 0,
+      local_search          : false,
+      local_search_mode     : LocalSearchMode::WSAT,
+      local_search_dbg_flips: false,
+
+      share_max_size   : 40,
+      share_max_glue   : 8,
+      share_always_glue: 2,
+
+      binspr          : false,
+      cut_simplify    : false,
+      cut_delay       : 2,
+      cut_aig         : false,
+      cut_lut         : false,
+      cut_xor         : false,
+      cut_npn3        : false,
+      cut_dont_cares  : true,
+      cut_redundancies: true,
+      cut_force       : false,
+      anf_simplify    : false,
+      anf_delay       : 2,
+      anf_exlin       : false,
+
+      lookahead_simplify             : false,
+      lookahead_simplify_bca         : true,
+      lookahead_cube_cutoff          : CutoffType::Depth,
+      lookahead_cube_fraction        : 0.4,
+      lookahead_cube_depth           : 1,
+      lookahead_cube_freevars        : 0.8,
+      lookahead_cube_psat_var_exp    : 1.0,
+      lookahead_cube_psat_clause_base: 2.0,
+      lookahead_cube_psat_trigger    : 5.0,
+      lookahead_reward               : RewardType::MarchCu,
+      lookahead_f64                  : true,
+      lookahead_global_autarky       : false,
+      lookahead_delta_fraction       : 1.0,
+      lookahead_use_learned          : false,
+
+      incremental   : false,
+      next_simplify1: 30000,
+      simplify_mult2: 1.5,
+      simplify_max  : 500000,
+      simplify_delay: 0,
+      variable_decay: 110,
+
+      gc_strategy      : GcStrategy::GluePsm,
+      gc_activity_decay: 0.999,
+      gc_initial       : 20000,
+      gc_increment     : 500,
+      gc_small_lbd     : 3,
+      gc_glue_factor   : 0.0,
+      no_gc            : false,
+      gc_k             : 7,
+      gc_burst         : false,
+      gc_defrag        : true,
+      force_cleanup    : false,
+
+      backtrack_scopes        : 100,
+      backtrack_init_conflicts: 4000,
+      minimize_lemmas         : true,
+      minimize_lemmas_binary  : true,
+      dyn_sub_res             : true,
+      core_minimize           : false,
+      core_minimize_partial   : false,
+
+      drat            : false,
+      drat_binary     : false,
+      drat_file       : SymbolData::Null,
+      drat_check_unsat: false,
+      drat_check_sat  : false,
+      drat_activity   : false,
+      card_solver     : true,
+      xor_solver      : false,
+      pb_resolve      : PbResolve::Cardinality,
+      pb_lemma_format : PbLemmaFormat::Cardinality,
+
+      branching_heuristic: BranchingHeuristic::Vsids,
+      anti_exploration   : false,
+      step_size_init     : 0.40,
+      step_size_dec      : 0.000001,
+      step_size_min      : 0.06,
+      reward_multiplier  : 0.9,
+      reward_offset      : 1000000.0,
+
+      elim_vars: true,
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   #[test]
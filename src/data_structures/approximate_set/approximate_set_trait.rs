@@ -38,3 +38,113 @@ pub trait ApproximateSet<T> {
     Self::make_intersection(self, other).empty()
   }
 }
+
+/// Inserts `inserted` into an empty `S` and returns the fraction of `queried` that it claims to
+/// contain. The values of `queried` should not occur in `inserted`, so that every claimed member is
+/// a false positive. Useful for choosing an `ApproximateSet` for a given distribution of values.
+pub fn estimate_false_positive_rate<S: ApproximateSet<u32>>(inserted: &[u32], queried: &[u32]) -> f64 {
+  if queried.is_empty() {
+    return 0.0;
+  }
+  let set             = S::with_values(inserted);
+  let false_positives = queried.iter().filter(|&value| set.may_contain(value)).count();
+  false_positives as f64 / queried.len() as f64
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::OredIntegerSet;
+
+  /// A 256 bit Bloom filter with two hash functions.
+  #[derive(Clone, Eq, PartialEq)]
+  struct BloomSet {
+    bits: [u64; 4],
+  }
+
+  impl BloomSet {
+    fn hashes(value: u32) -> [usize; 2] {
+      [
+        (value.wrapping_mul(0x9E3779B1) >> 24) as usize,
+        (value.wrapping_mul(0x85EBCA77) >> 24) as usize,
+      ]
+    }
+
+    fn has_bit(&self, bit: usize) -> bool {
+      self.bits[bit / 64] & (1 << (bit % 64)) != 0
+    }
+  }
+
+  impl ApproximateSet<u32> for BloomSet {
+    fn new() -> Self {
+      BloomSet { bits: [0; 4] }
+    }
+
+    fn with_value(value: &u32) -> Self {
+      Self::with_values(&[*value])
+    }
+
+    fn with_values(values: &[u32]) -> Self {
+      let mut set = Self::new();
+      for value in values {
+        set.insert(value);
+      }
+      set
+    }
+
+    fn insert(&mut self, value: &u32) {
+      for bit in Self::hashes(*value) {
+        self.bits[bit / 64] |= 1 << (bit % 64);
+      }
+    }
+
+    fn may_contain(&self, value: &u32) -> bool {
+      Self::hashes(*value).iter().all(|&bit| self.has_bit(bit))
+    }
+
+    fn make_union(a: &Self, b: &Self) -> Self {
+      let mut set = a.clone();
+      for (word, other) in set.bits.iter_mut().zip(b.bits.iter()) {
+        *word |= other;
+      }
+      set
+    }
+
+    fn make_intersection(a: &Self, b: &Self) -> Self {
+      let mut set = a.clone();
+      for (word, other) in set.bits.iter_mut().zip(b.bits.iter()) {
+        *word &= other;
+      }
+      set
+    }
+
+    fn empty(&self) -> bool {
+      self.bits.iter().all(|&word| word == 0)
+    }
+
+    fn may_equal(&self, other: &Self) -> bool {
+      self == other
+    }
+
+    fn equivalent(&self, other: &Self) -> bool {
+      self == other
+    }
+
+    fn reset(&mut self) {
+      self.bits = [0; 4];
+    }
+  }
+
+  #[test]
+  fn bloom_set_has_fewer_false_positives() {
+    let inserted: Vec<u32> = (1..=8).collect();
+    let queried : Vec<u32> = (9..=100).collect();
+
+    let ored  = estimate_false_positive_rate::<OredIntegerSet<u64, u32>>(&inserted, &queried);
+    let bloom = estimate_false_positive_rate::<BloomSet>(&inserted, &queried);
+    // The ored set of 1 through 8 has the low four bits set, so it claims nearly everything.
+    assert!(ored > 0.9);
+    assert!(bloom < 0.1);
+  }
+}
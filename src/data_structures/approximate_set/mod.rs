@@ -10,5 +10,5 @@ mod approximate_set_trait;
 mod ored_integer_set;
 
 
-pub use approximate_set_trait::ApproximateSet;
+pub use approximate_set_trait::{estimate_false_positive_rate, ApproximateSet};
 pub use ored_integer_set::OredIntegerSet;
@@ -0,0 +1,51 @@
+/*!
+
+The Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, 1, ..., the restart schedule that
+Luby, Sinclair, and Zuckerman showed to be within a logarithmic factor of optimal for Las Vegas
+algorithms. Restart intervals are usually the terms scaled by a constant. Terms are memoized per
+thread, since a schedule asks for them in order.
+
+*/
+
+use std::cell::RefCell;
+
+thread_local! {
+  // `TERMS[i - 1]` is `luby(i)`.
+  static TERMS: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+}
+
+/// The `i`th term of the Luby sequence, counting from 1.
+pub fn luby(i: u32) -> u32 {
+  assert!(i > 0, "the Luby sequence starts at index 1");
+
+  TERMS.with(|terms| {
+    let mut terms = terms.borrow_mut();
+    while terms.len() < i as usize {
+      let n    = terms.len() as u32 + 1;
+      // The largest `k` with `2^k <= n + 1`.
+      let k    = 31 - (n + 1).leading_zeros();
+      let term = if n + 1 == 1 << k {
+        1 << (k - 1)
+      } else {
+        terms[(n - (1 << k) + 1) as usize - 1]
+      };
+      terms.push(term);
+    }
+    terms[i as usize - 1]
+  })
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn first_terms_are_canonical() {
+    let expected = [1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, 1];
+    let terms: Vec<u32> = (1..=16).map(luby).collect();
+    assert_eq!(terms, expected);
+    // Memoized terms are the same when asked for again.
+    assert_eq!(luby(15), 8);
+  }
+}
@@ -9,12 +9,18 @@ mod random;
 mod true_false_vectors;
 mod approximate_set;
 mod statistics;
+mod timestamped_marks;
+mod luby;
+mod stopwatch;
 
 pub use moving_average::{EMA, ExponentialMovingAverage};
 pub use random::RandomGenerator;
 pub use true_false_vectors::TFVectors;
-pub use approximate_set::{ApproximateSet, OredIntegerSet};
+pub use approximate_set::{estimate_false_positive_rate, ApproximateSet, OredIntegerSet};
 pub use statistics::{Statistic, Statistics};
+pub use timestamped_marks::TimestampedMarks;
+pub use luby::luby;
+pub use stopwatch::Stopwatch;
 pub use vector_pool::*;
 
 /*
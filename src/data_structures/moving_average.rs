@@ -36,7 +36,7 @@ pub struct ExponentialMovingAverage {
 
 impl Display for ExponentialMovingAverage {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    self.mean.fmt(f)
+    self.mean().fmt(f)
   }
 }
 
@@ -44,7 +44,7 @@ impl ExponentialMovingAverage {
 
   #[cfg(feature = "debug")]
   pub fn invariant(&self) -> bool {
-    verify!(0f64 <= self.alpha && self.alpha <= self.beta && self.beta <= 1f64)
+    0f64 <= self.alpha && self.alpha <= self.beta && self.beta <= 1f64
   }
 
   pub fn new(alpha: f64) -> Self {
@@ -58,7 +58,7 @@ impl ExponentialMovingAverage {
         };
 
     #[cfg(feature = "debug")]
-    verify!(0f64 <= self.alpha && self.alpha <= self.beta && self.beta <= 1f64);
+    verify!(new_ema.invariant());
 
     new_ema
   }
@@ -67,7 +67,7 @@ impl ExponentialMovingAverage {
     self.alpha = alpha;
 
     #[cfg(feature = "debug")]
-    verify!(0f64 <= self.alpha && self.alpha <= self.beta && self.beta <= 1f64);
+    verify!(self.invariant());
   }
 
   pub fn update(&mut self, value: f64) {
@@ -85,7 +85,6 @@ impl ExponentialMovingAverage {
       self.wait -= 1;
       return;
     }
-    self.wait -= 1;
     self.period = 2*(self.period + 1) - 1;
     self.wait = self.period;
     self.beta *= 0.5;
@@ -99,6 +98,15 @@ impl ExponentialMovingAverage {
     self.value = value;
   }
 
+  /// Restarts the cooling schedule and clears the value, keeping `alpha`. Afterward `self` behaves
+  /// exactly like a freshly constructed `ExponentialMovingAverage::new(alpha)`.
+  pub fn reset(&mut self) {
+    self.beta   = 1f64;
+    self.value  = 0f64;
+    self.period = 0u32;
+    self.wait   = 0u32;
+  }
+
   pub fn mean(&self) -> f64 {
     self.value
   }
@@ -121,8 +129,27 @@ impl From<EMA> for f64 {
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  #[test]
+  fn reset_behaves_like_new() {
+    let mut reused = EMA::new(0.03);
+    for x in 0..100 {
+      reused.update(x as f64);
+    }
+    reused.reset();
+    assert_eq!(reused, EMA::new(0.03));
+
+    let mut fresh = EMA::new(0.03);
+    for x in [5.0, 1.0, 7.0, 3.0, 2.0].iter() {
+      reused.update(*x);
+      fresh.update(*x);
+    }
+    assert_eq!(reused.mean(), fresh.mean());
+  }
 }
@@ -26,14 +26,20 @@ impl RandomGenerator {
   }
 
   pub fn next(&mut self) -> u32 {
-    self.data = self.data * 214013 + 2531011;
-    (self.data >> 16) & MAX_VALUE
+    // The recurrence relies on wrapping arithmetic, as in C++.
+    self.data = self.data.wrapping_mul(214013).wrapping_add(2531011);
+    (self.data >> 16) & Self::MAX_VALUE
   }
 
   pub fn at_most(&mut self, n: u32) -> u32 {
     self.next() % n
   }
 
+  /// A number in `[0, 1)`, in steps of `1 / 0x8000`.
+  pub fn gen_f64(&mut self) -> f64 {
+    self.next() as f64 / (Self::MAX_VALUE as f64 + 1.0)
+  }
+
 }
 
 impl FnOnce<()> for RandomGenerator {
@@ -6,17 +6,91 @@
 */
 
 use std::collections::HashMap;
+use std::collections::hash_map::Iter;
 use std::fmt::{Display, Formatter};
+use std::ops::Index;
 
 
-pub type Statistics = HashMap<&'static str, Statistic>;
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Statistics {
+  statistics: HashMap<&'static str, Statistic>
+}
+
+impl Statistics {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds `value` to the statistic named `key`, inserting it if it is not already present. As in z3,
+  /// repeated updates of the same key accumulate.
+  pub fn update(&mut self, key: &'static str, value: impl Into<Statistic>) {
+    let value = value.into();
+    self.statistics
+        .entry(key)
+        .and_modify(|existing| *existing = *existing + value)
+        .or_insert(value);
+  }
+
+  /// Accumulates every statistic of `other` into `self`.
+  pub fn extend(&mut self, other: &Statistics) {
+    for (&key, &value) in other.iter() {
+      self.update(key, value);
+    }
+  }
+
+  pub fn get(&self, key: &str) -> Option<&Statistic> {
+    self.statistics.get(key)
+  }
+
+  pub fn contains_key(&self, key: &str) -> bool {
+    self.statistics.contains_key(key)
+  }
+
+  pub fn iter(&self) -> Iter<'_, &'static str, Statistic> {
+    self.statistics.iter()
+  }
+
+  pub fn len(&self) -> usize {
+    self.statistics.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.statistics.is_empty()
+  }
+
+  pub fn clear(&mut self) {
+    self.statistics.clear()
+  }
+}
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+impl Index<&str> for Statistics {
+  type Output = Statistic;
+
+  fn index(&self, index: &str) -> &Self::Output {
+    self.statistics.index(index)
+  }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Statistic {
   Integer(usize),
   Float(f64)
 }
 
+impl std::ops::Add for Statistic {
+  type Output = Statistic;
+
+  /// Integers add as integers. Mixing in a float promotes the result to a float.
+  fn add(self, rhs: Self) -> Self::Output {
+    match (self, rhs) {
+      (Statistic::Integer(n), Statistic::Integer(m)) => Statistic::Integer(n + m),
+      (Statistic::Integer(n), Statistic::Float(r))   => Statistic::Float(n as f64 + r),
+      (Statistic::Float(r),   Statistic::Integer(n)) => Statistic::Float(r + n as f64),
+      (Statistic::Float(r),   Statistic::Float(s))   => Statistic::Float(r + s),
+    }
+  }
+}
+
 impl From<f64> for Statistic {
   fn from(r: f64) -> Self {
     Statistic::Float(r)
@@ -0,0 +1,55 @@
+/*!
+
+A `Stopwatch` accumulates the wall-clock time between calls to `start` and `stop`, across any number
+of such intervals, like z3's `stopwatch`.
+
+*/
+
+use std::time::{Duration, Instant};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct Stopwatch {
+  started: Option<Instant>, // When the running interval began, or `None` if stopped
+  elapsed: Duration,        // The total of the finished intervals
+}
+
+impl Stopwatch {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Starts a new interval. Does nothing if the stopwatch is already running.
+  pub fn start(&mut self) {
+    if self.started.is_none() {
+      self.started = Some(Instant::now());
+    }
+  }
+
+  /// Ends the running interval, adding it to the elapsed time.
+  pub fn stop(&mut self) {
+    if let Some(started) = self.started.take() {
+      self.elapsed += started.elapsed();
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.started = None;
+    self.elapsed = Duration::ZERO;
+  }
+
+  pub fn is_running(&self) -> bool {
+    self.started.is_some()
+  }
+
+  /// The total elapsed time, including the running interval.
+  pub fn elapsed(&self) -> Duration {
+    match self.started {
+      Some(started) => self.elapsed + started.elapsed(),
+      None          => self.elapsed,
+    }
+  }
+
+  pub fn elapsed_seconds(&self) -> f64 {
+    self.elapsed().as_secs_f64()
+  }
+}
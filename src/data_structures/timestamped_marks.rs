@@ -0,0 +1,104 @@
+/*!
+
+A set of marks on the indices `0..n` that can be cleared in constant time. Each index stores the
+timestamp at which it was last marked, and an index is marked exactly when its stamp equals the
+current timestamp. Clearing all marks just advances the timestamp.
+
+This is the `visited`/`visited_ts` idiom from z3, used during conflict analysis where the set is
+cleared after every conflict but only a few indices are ever marked.
+
+*/
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct TimestampedMarks {
+  stamps   : Vec<u32>,
+  timestamp: u32,
+}
+
+impl TimestampedMarks {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_capacity(capacity: usize) -> Self {
+    TimestampedMarks {
+      stamps   : vec![0; capacity],
+      timestamp: 1,
+    }
+  }
+
+  /// Makes room for the indices `0..capacity`.
+  pub fn reserve(&mut self, capacity: usize) {
+    if self.stamps.len() < capacity {
+      self.stamps.resize(capacity, 0);
+    }
+  }
+
+  pub fn mark(&mut self, index: usize) {
+    self.reserve(index + 1);
+    self.stamps[index] = self.timestamp;
+  }
+
+  pub fn unmark(&mut self, index: usize) {
+    if let Some(stamp) = self.stamps.get_mut(index) {
+      *stamp = 0;
+    }
+  }
+
+  pub fn is_marked(&self, index: usize) -> bool {
+    self.stamps.get(index) == Some(&self.timestamp)
+  }
+
+  /// Unmarks every index by advancing the timestamp. Only when the timestamp wraps around do we
+  /// actually touch the stamps.
+  pub fn clear_all(&mut self) {
+    if self.timestamp == u32::MAX {
+      self.stamps.iter_mut().for_each(|stamp| *stamp = 0);
+      self.timestamp = 0;
+    }
+    self.timestamp += 1;
+  }
+}
+
+impl Default for TimestampedMarks {
+  fn default() -> Self {
+    TimestampedMarks::with_capacity(0)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn clear_all_unmarks_without_touching_stamps() {
+    let mut marks = TimestampedMarks::with_capacity(4);
+    for index in [0, 2, 7] {
+      marks.mark(index);
+    }
+    assert!(marks.is_marked(0) && marks.is_marked(2) && marks.is_marked(7));
+    assert!(!marks.is_marked(1) && !marks.is_marked(100));
+
+    marks.clear_all();
+    for index in 0..8 {
+      assert!(!marks.is_marked(index));
+    }
+
+    marks.mark(2);
+    assert!(marks.is_marked(2));
+    marks.unmark(2);
+    assert!(!marks.is_marked(2));
+  }
+
+  #[test]
+  fn clear_all_survives_timestamp_wraparound() {
+    let mut marks = TimestampedMarks::with_capacity(2);
+    marks.timestamp = u32::MAX;
+    marks.mark(1);
+
+    marks.clear_all();
+    assert!(!marks.is_marked(0));
+    assert!(!marks.is_marked(1));
+  }
+}
@@ -0,0 +1,402 @@
+/*!
+
+Reading problems in the DIMACS CNF format. A DIMACS file consists of a header line
+`p cnf <variables> <clauses>` followed by clauses, each a whitespace separated list of nonzero
+integers terminated by `0`. The integer `v` stands for the variable `v - 1` and `-v` for its
+negation. Lines starting with `c` are comments. Some benchmarks end with a line `%`, after which
+everything is ignored.
+
+Benchmark sets are commonly distributed gzipped, so `read_dimacs_file` decompresses files ending in
+`.gz` before handing their contents to the parser.
+
+The incremental iCNF format has the header `p inccnf` and no counts. Clauses are interleaved with
+queries, lines `a <literals> 0` that ask whether the clauses so far are satisfiable under the
+assumptions `<literals>`.
+
+The weighted WCNF format, used for MaxSAT, has the header `p wcnf <variables> <clauses> <top>` and
+prefixes each clause with its weight. Clauses of weight at least `top` are hard, and the rest are
+soft. The newer header-free variant marks hard clauses with `h` instead of a weight.
+
+*/
+
+use std::{
+  fs::File,
+  io::{self, Read, Write},
+  path::Path,
+};
+
+use flate2::read::GzDecoder;
+
+use crate::{
+  clause::{Clause, ClauseVector, ClauseWrapper, ClauseWrapperVector},
+  errors::Error,
+  Literal,
+  LiteralVector,
+};
+
+/// Parses DIMACS CNF `input`, returning the declared number of variables and the clauses. It is an
+/// error for the header to be malformed, repeated, or missing, for a literal to mention a variable
+/// beyond the declared count, or for the last clause to lack its terminating `0`.
+pub fn parse_dimacs_cnf(input: &str) -> Result<(u32, ClauseVector), Error> {
+  let mut variable_count        = None;
+  let mut clauses: ClauseVector = Vec::new();
+  let mut literals              = LiteralVector::new();
+  let mut last_line             = 0;
+
+  for (line_number, line) in input.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('c') {
+      continue;
+    }
+    if line.starts_with('%') {
+      break;
+    }
+    last_line = line_number;
+
+    if line.starts_with('p') {
+      if variable_count.is_some() {
+        return Err(dimacs_error(line_number, "repeated `p cnf` header"));
+      }
+      match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        ["p", "cnf", variables, clause_count] => {
+          let variables = variables.parse::<u32>();
+          let count     = clause_count.parse::<u32>();
+          match (variables, count) {
+            (Ok(variables), Ok(_)) => variable_count = Some(variables),
+            _                      => return Err(dimacs_error(line_number, "malformed `p cnf` header")),
+          }
+        }
+        _ => return Err(dimacs_error(line_number, "malformed `p cnf` header")),
+      }
+      continue;
+    }
+
+    let declared = match variable_count {
+      Some(declared) => declared,
+      None           => return Err(dimacs_error(line_number, "clause before the `p cnf` header")),
+    };
+    for token in line.split_whitespace() {
+      match parse_literal(token, line_number, line)? {
+        Some(literal) if literal.var() as u32 >= declared => {
+          return Err(dimacs_error(
+            line_number,
+            &format!("variable {} exceeds the declared {} variables", literal.var() + 1, declared)
+          ));
+        }
+        Some(literal) => literals.push(literal),
+        None          => clauses.push(Clause::new(clauses.len() as u32, std::mem::take(&mut literals), false)),
+      }
+    }
+  }
+
+  if !literals.is_empty() {
+    return Err(dimacs_error(last_line, "the last clause is missing its terminating `0`"));
+  }
+  match variable_count {
+    Some(variable_count) => Ok((variable_count, clauses)),
+    None                 => Err(Error::DimacsParse { line: 0, message: "missing `p cnf` header".to_string() }),
+  }
+}
+
+/// A clause or query of an iCNF problem, in the order they appear.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum IcnfCommand {
+  AddClause(LiteralVector),
+  Assume(LiteralVector),
+}
+
+/// Parses iCNF `input` into its clauses and queries. As with `parse_dimacs_cnf`, it is an error for
+/// the last clause to lack its terminating `0`.
+pub fn parse_icnf(input: &str) -> Result<Vec<IcnfCommand>, Error> {
+  let mut commands  = Vec::new();
+  let mut literals  = LiteralVector::new();
+  let mut last_line = 0;
+
+  for (line_number, line) in input.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('c') {
+      continue;
+    }
+    last_line = line_number;
+
+    if line.starts_with('p') {
+      match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        ["p", "inccnf"] => continue,
+        _               => return Err(parse_error(line_number, line)),
+      }
+    }
+
+    if let Some(assumptions) = line.strip_prefix('a') {
+      // A query must be complete on its line and may not interrupt a clause.
+      if !literals.is_empty() {
+        return Err(parse_error(line_number, line));
+      }
+      let mut terminated = false;
+      for token in assumptions.split_whitespace() {
+        if terminated {
+          return Err(parse_error(line_number, line));
+        }
+        match parse_literal(token, line_number, line)? {
+          Some(literal) => literals.push(literal),
+          None          => terminated = true,
+        }
+      }
+      if !terminated {
+        return Err(parse_error(line_number, line));
+      }
+      commands.push(IcnfCommand::Assume(std::mem::take(&mut literals)));
+      continue;
+    }
+
+    for token in line.split_whitespace() {
+      match parse_literal(token, line_number, line)? {
+        Some(literal) => literals.push(literal),
+        None          => commands.push(IcnfCommand::AddClause(std::mem::take(&mut literals))),
+      }
+    }
+  }
+
+  if !literals.is_empty() {
+    return Err(dimacs_error(last_line, "the last clause is missing its terminating `0`"));
+  }
+  Ok(commands)
+}
+
+/// A WCNF problem: the number of variables, the hard clauses, and the soft clauses with their weights.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct WcnfProblem {
+  pub variable_count: u32,
+  pub hard          : Vec<LiteralVector>,
+  pub soft          : Vec<(LiteralVector, u64)>,
+}
+
+/// Parses WCNF `input`, with or without a `p wcnf` header. Without a header, the number of variables
+/// is the largest variable mentioned. With one, it is an error for a literal to mention a variable
+/// beyond the declared count. Either way, it is an error for the last clause, or a weight with no
+/// clause after it, to lack its terminating `0`.
+pub fn parse_wcnf(input: &str) -> Result<WcnfProblem, Error> {
+  let mut problem   = WcnfProblem::default();
+  let mut declared  = None;
+  let mut top       = None;
+  let mut weight    = None; // `Some(None)` for a hard clause, `Some(Some(w))` for a soft one
+  let mut literals  = LiteralVector::new();
+  let mut last_line = 0;
+
+  for (line_number, line) in input.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('c') {
+      continue;
+    }
+    last_line = line_number;
+
+    if line.starts_with('p') {
+      match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        ["p", "wcnf", variables, _clauses, rest @ ..] if rest.len() <= 1 => {
+          problem.variable_count = variables.parse::<u32>().map_err(|_| parse_error(line_number, line))?;
+          declared               = Some(problem.variable_count);
+          if let Some(value) = rest.first() {
+            top = Some(value.parse::<u64>().map_err(|_| parse_error(line_number, line))?);
+          }
+        }
+        _ => return Err(parse_error(line_number, line)),
+      }
+      continue;
+    }
+
+    for token in line.split_whitespace() {
+      let clause_weight = match weight {
+        Some(clause_weight) => clause_weight,
+        None => {
+          weight = match token {
+            "h" => Some(None),
+            _   => {
+              let value = token.parse::<u64>().map_err(|_| parse_error(line_number, line))?;
+              Some(if top.map_or(false, |top| value >= top) { None } else { Some(value) })
+            }
+          };
+          continue;
+        }
+      };
+
+      match parse_literal(token, line_number, line)? {
+        Some(literal) if declared.map_or(false, |declared| literal.var() as u32 >= declared) => {
+          return Err(dimacs_error(
+            line_number,
+            &format!("variable {} exceeds the declared {} variables", literal.var() + 1, problem.variable_count)
+          ));
+        }
+        Some(literal) => {
+          problem.variable_count = u32::max(problem.variable_count, literal.var() as u32 + 1);
+          literals.push(literal);
+        }
+        None => {
+          let clause = std::mem::take(&mut literals);
+          match clause_weight {
+            Some(clause_weight) => problem.soft.push((clause, clause_weight)),
+            None                => problem.hard.push(clause),
+          }
+          weight = None;
+        }
+      }
+    }
+  }
+
+  if weight.is_some() {
+    return Err(dimacs_error(last_line, "the last clause is missing its terminating `0`"));
+  }
+  Ok(problem)
+}
+
+/// Writes `clauses` over `variable_count` variables in DIMACS CNF. A clause containing
+/// `Literal::NULL` is rejected with `io::ErrorKind::InvalidInput`.
+pub fn write_dimacs_cnf(clauses: &ClauseVector, variable_count: u32, out: &mut impl Write) -> io::Result<()> {
+  writeln!(out, "p cnf {} {}", variable_count, clauses.len())?;
+  for clause in clauses.iter() {
+    write_dimacs_clause(clause.literals(), out)?;
+  }
+  Ok(())
+}
+
+/// Like `write_dimacs_cnf`, but for clauses that may be stored as `ClauseWrapper::Binary`.
+pub fn write_dimacs_cnf_wrapped(
+  clauses       : &ClauseWrapperVector,
+  variable_count: u32,
+  out           : &mut impl Write
+) -> io::Result<()>
+{
+  writeln!(out, "p cnf {} {}", variable_count, clauses.len())?;
+  for clause in clauses.iter() {
+    match clause {
+      ClauseWrapper::Binary { literal1, literal2, .. } => write_dimacs_clause(&[*literal1, *literal2], out)?,
+      ClauseWrapper::Nonbinary(clause)                 => write_dimacs_clause(clause.literals(), out)?,
+    }
+  }
+  Ok(())
+}
+
+fn write_dimacs_clause(literals: &[Literal], out: &mut impl Write) -> io::Result<()> {
+  for literal in literals.iter() {
+    if *literal == Literal::NULL {
+      return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot write the null literal as DIMACS"));
+    }
+    write!(out, "{} ", literal.to_dimacs())?;
+  }
+  writeln!(out, "0")
+}
+
+/// Reads the contents of the DIMACS file at `path`, decompressing it if its extension is `.gz`.
+pub fn read_dimacs_file(path: &Path) -> Result<String, Error> {
+  let mut file     = File::open(path).map_err(io_error)?;
+  let mut contents = String::new();
+
+  match path.extension().and_then(|extension| extension.to_str()) {
+    Some("gz") => GzDecoder::new(file).read_to_string(&mut contents),
+    _          => file.read_to_string(&mut contents),
+  }.map_err(io_error)?;
+
+  Ok(contents)
+}
+
+/// Parses a DIMACS literal, or returns `None` for the terminating `0`.
+fn parse_literal(token: &str, line_number: usize, line: &str) -> Result<Option<Literal>, Error> {
+  match token.parse::<i32>() {
+    Ok(0)     => Ok(None),
+    Ok(value) => Ok(Some(Literal::from_dimacs(value))),
+    Err(_)    => Err(parse_error(line_number, line)),
+  }
+}
+
+fn parse_error(line_number: usize, line: &str) -> Error {
+  dimacs_error(line_number, &format!("unexpected `{}`", line))
+}
+
+fn dimacs_error(line_number: usize, message: &str) -> Error {
+  Error::DimacsParse { line: line_number + 1, message: message.to_string() }
+}
+
+fn io_error(error: std::io::Error) -> Error {
+  Error::DimacsIo(error.to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn error_message(input: &str) -> String {
+    match parse_dimacs_cnf(input) {
+      Err(Error::DimacsParse { message, .. }) => message,
+      result                                  => panic!("expected a parse error, got {:?}", result),
+    }
+  }
+
+  #[test]
+  fn parses_clauses_up_to_the_end_marker() {
+    let (variable_count, clauses) = parse_dimacs_cnf("c example\np cnf 3 2\n1 -3 0\n2\n3 0\n%\n0\n").unwrap();
+    assert_eq!(variable_count, 3);
+    assert_eq!(clauses.len(), 2);
+    assert_eq!(clauses[0].literals(), &vec![Literal::new(0, false), Literal::new(2, true)]);
+    assert_eq!(clauses[1].literals(), &vec![Literal::new(1, false), Literal::new(2, false)]);
+  }
+
+  #[test]
+  fn rejects_malformed_input() {
+    assert!(error_message("p cnf three 1\n1 0\n").contains("header"));
+    assert!(error_message("p cnf 2 1\n1 3 0\n").contains("exceeds"));
+    assert!(error_message("p cnf 2 1\n1 2\n").contains("terminating"));
+    assert!(error_message("1 2 0\n").contains("before"));
+  }
+
+  #[test]
+  fn icnf_rejects_an_unterminated_clause() {
+    let commands = parse_icnf("p inccnf\n1 -2 0\na 1 0\n").unwrap();
+    assert_eq!(commands.len(), 2);
+
+    match parse_icnf("p inccnf\n1 -2 0\n2 3\n") {
+      Err(Error::DimacsParse { line, message }) => {
+        assert_eq!(line, 3);
+        assert!(message.contains("terminating"));
+      }
+      result => panic!("expected a parse error, got {:?}", result),
+    }
+  }
+
+  #[test]
+  fn wcnf_rejects_malformed_input() {
+    let problem = parse_wcnf("p wcnf 2 2 10\n10 1 -2 0\n3 2 0\n").unwrap();
+    assert_eq!(problem.hard, vec![vec![Literal::from_dimacs(1), Literal::from_dimacs(-2)]]);
+    assert_eq!(problem.soft, vec![(vec![Literal::from_dimacs(2)], 3)]);
+
+    for input in ["p wcnf 2 1 10\n3 1 2\n", "p wcnf 2 1 10\n3\n", "p wcnf 2 1 10\n3 1 3 0\n"] {
+      match parse_wcnf(input) {
+        Err(Error::DimacsParse { line, .. }) => assert_eq!(line, 2),
+        result                               => panic!("expected a parse error, got {:?}", result),
+      }
+    }
+  }
+
+  #[test]
+  fn written_clauses_parse_back() {
+    let input                     = "p cnf 4 3\n1 -2 0\n-1 3 4 0\n2 0\n";
+    let (variable_count, clauses) = parse_dimacs_cnf(input).unwrap();
+
+    let mut output = Vec::new();
+    write_dimacs_cnf(&clauses, variable_count, &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), input);
+
+    let wrapped = vec![
+      ClauseWrapper::Binary { literal1: Literal::from_dimacs(1), literal2: Literal::from_dimacs(-2), is_learned: false },
+      ClauseWrapper::Nonbinary(Box::new(clauses[1].clone())),
+    ];
+    let mut output = Vec::new();
+    write_dimacs_cnf_wrapped(&wrapped, variable_count, &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "p cnf 4 2\n1 -2 0\n-1 3 4 0\n");
+  }
+
+  #[test]
+  fn null_literal_is_not_written() {
+    let clauses = vec![Clause::new(0, vec![Literal::from_dimacs(1), Literal::NULL], false)];
+    let error   = write_dimacs_cnf(&clauses, 1, &mut Vec::new()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+  }
+}
@@ -14,12 +14,24 @@ pub enum Error {
   #[error("Local search is incomplete with extensions beyond PB.")]
   IncompleteExtension,
 
+  #[error("The operation requires the solver to be at the base level.")]
+  NotAtBaseLevel,
+
   #[error("Module has no parameters file or file not found.")]
   DeserializeParametersFile,
 
   #[error("An error occurred while deserializing the parameters.")]
   DeserializeParameterValue,
 
+  #[error("Could not read DIMACS file: {0}")]
+  DimacsIo(String),
+
+  #[error("Malformed DIMACS input on line {line}: {message}")]
+  DimacsParse { line: usize, message: String },
+
+  #[error("Could not read serialized clause database: {0}")]
+  Deserialize(String),
+
   // todo: Is this a real error or is it an Unknown error?
   #[error("A Default Error occurred.")]
   Default,
@@ -0,0 +1,95 @@
+/*!
+
+A `Justification` records why a literal was assigned: the decision level of the assignment together
+with its reason, which can be a(n):
+
+  1) nothing                 : for decisions, assumptions, and units
+  2) literal                 : for literals propagated by a binary clause, the other literal of the clause
+  3) pair of literals        : for literals propagated by a ternary clause, the other literals of the clause
+  4) clause offset           : for literals propagated by a clause in the clause allocator
+  5) external justification  : for literals propagated by an extension
+
+As with the watch lists, there are no clause objects for binary and ternary clauses, so their reasons
+store the other literals of the clause directly. The literals of a reason are false under the
+assignment; their negations are the antecedents of the justified literal.
+
+A conflict is a justification, too, namely one for false.
+
+*/
+
+use std::fmt::{Display, Formatter};
+
+use crate::{
+  clause::ClauseOffset,
+  ExternalJustificationIndex,
+  Literal,
+};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Reason {
+  None,
+  Binary(Literal),
+  Ternary(Literal, Literal),
+  Clause(ClauseOffset),
+  External(ExternalJustificationIndex),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Justification {
+  level : u32,
+  reason: Reason,
+}
+
+impl Default for Justification {
+  fn default() -> Self {
+    Justification::with_level(0)
+  }
+}
+
+impl Justification {
+  /// A justification without a reason, as for decisions and units.
+  pub fn with_level(level: u32) -> Self {
+    Justification { level, reason: Reason::None }
+  }
+
+  pub fn binary(level: u32, literal: Literal) -> Self {
+    Justification { level, reason: Reason::Binary(literal) }
+  }
+
+  pub fn ternary(level: u32, literal1: Literal, literal2: Literal) -> Self {
+    Justification { level, reason: Reason::Ternary(literal1, literal2) }
+  }
+
+  pub fn clause(level: u32, clause_offset: ClauseOffset) -> Self {
+    Justification { level, reason: Reason::Clause(clause_offset) }
+  }
+
+  pub fn external(level: u32, index: ExternalJustificationIndex) -> Self {
+    Justification { level, reason: Reason::External(index) }
+  }
+
+  pub fn level(&self) -> u32 {
+    self.level
+  }
+
+  pub fn reason(&self) -> Reason {
+    self.reason
+  }
+
+  pub fn is_none(&self) -> bool {
+    self.reason == Reason::None
+  }
+}
+
+impl Display for Justification {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self.reason {
+      Reason::None                         => write!(f, "none"),
+      Reason::Binary(literal)              => write!(f, "binary: {}", literal),
+      Reason::Ternary(literal1, literal2)  => write!(f, "ternary: {} {}", literal1, literal2),
+      Reason::Clause(clause_offset)        => write!(f, "clause: {}", clause_offset),
+      Reason::External(index)              => write!(f, "external: {}", index),
+    }?;
+    write!(f, " @{}", self.level)
+  }
+}
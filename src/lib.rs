@@ -13,24 +13,51 @@ mod missing_types;  // todo: delete this.
 mod config;
 mod log;
 mod data_structures;
+mod dimacs;
 mod watched;
 mod clause;
+mod clause_allocator;
 mod parameters;
 mod parallel;
+mod variable_queue;
+mod branching;
+mod justification;
+mod mus;
+mod serialize;
+mod observer;
 
 
 // Re-exported items
-pub use data_structures::{OredIntegerSet, Statistic, Statistics};
+pub use branching::{BranchingStrategy, Chb, Vsids};
+pub use check_satisfiability::CheckResult;
+pub use clause::{Clause, ClauseVector, ClauseWrapper, ClauseWrapperVector};
+pub use data_structures::{
+  estimate_false_positive_rate,
+  luby,
+  ApproximateSet,
+  OredIntegerSet,
+  Statistic,
+  Statistics,
+};
+pub use dimacs::{
+  parse_dimacs_cnf,
+  parse_wcnf,
+  write_dimacs_cnf,
+  write_dimacs_cnf_wrapped,
+  WcnfProblem,
+};
 pub use errors::Error;
 pub use lifted_bool::LiftedBool;
 pub use literal::{Literal, LiteralVector};
+pub use log::{set_verbose_stream, set_verbosity, set_verbosity_level, Verbosity};
 pub use model::Model;
+pub use observer::SolverObserver;
 pub use resource_limit::{
   ResourceLimit,
   ScopedResourceLimit,
   ScopedSuspendedResourceLimit,
 };
-pub use solver::Solver;
+pub use solver::{Solver, SolverSnapshot, SolverStatistics};
 
 
 
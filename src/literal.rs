@@ -50,32 +50,70 @@ impl Literal {
     }
   }
 
+  /// The literal $x_v$.
+  pub const fn positive(v: BoolVariable) -> Literal {
+    Literal(v << 1)
+  }
+
+  /// The literal $\overline{x}_v$.
+  pub const fn negative(v: BoolVariable) -> Literal {
+    Literal((v << 1) | 1)
+  }
+
+  /// Converts a nonzero DIMACS integer to a literal. The DIMACS variable `v` is the variable `v - 1`,
+  /// and a negative integer denotes the negated literal.
+  pub fn from_dimacs(value: i32) -> Literal {
+    sassert!(value != 0);
+    Literal::new((value.unsigned_abs() - 1) as BoolVariable, value < 0)
+  }
+
+  /// The inverse of `from_dimacs`.
+  pub fn to_dimacs(&self) -> i32 {
+    let value = (self.var() + 1) as i32;
+    if self.sign() { -value } else { value }
+  }
+
   /// Gives the value this `Literal` represents.
   pub const fn var(&self) -> BoolVariable {
     self.0 >> 1
   }
 
-  /// Returns `true` if the `Literal` is tagged as true, otherwise `false`.
+  /// Returns `true` if the `Literal` is negated, otherwise `false`.
   // todo: Rename `sign` to, say, `is_positive` or `is_not_negated`. Likewise with `unsign` (abs).
   pub const fn sign(&self) -> bool {
     (self.0 & 1) != 0
   }
 
-  /// Returns the "unsigned" copy of `self`, a kind of absolute value.
+  /// Returns the "unsigned" copy of `self`, a kind of absolute value. The result is always the
+  /// positive literal of `self.var()`, so its `sign()` is `false`.
   pub const fn unsign(&self) ->Literal {
     // Observe that `!1` has LS bit zero and all other bits one.
     Literal(self.0 & !1)
   }
 
   /// In-place negation.
-  pub const fn negate(&mut self) {
+  pub fn negate(&mut self) {
     self.0 = self.0 ^ 1;
   }
 
+  /// Returns the negation of `self`, leaving `self` unchanged.
+  pub const fn negated(self) -> Literal {
+    Literal(self.0 ^ 1)
+  }
+
   /// Gives underlying `BoolVar` with sign encoded in LSB.
   pub const fn index(&self) -> BoolVariable {
     self.0
   }
+
+  /// Orders literals by variable, and the positive literal of a variable before the negative one.
+  /// With the current encoding this agrees with the derived `Ord`, but unlike `Ord` it does not
+  /// depend on how the sign is encoded.
+  pub fn cmp_by_var(&self, other: &Literal) -> std::cmp::Ordering {
+    self.var()
+        .cmp(&other.var())
+        .then(self.sign().cmp(&other.sign()))
+  }
 }
 
 impl Default for Literal {
@@ -89,7 +127,7 @@ impl std::ops::Not for Literal {
   type Output = Self;
 
   fn not(self) -> Self::Output {
-    Literal(self.0 ^ 1)
+    self.negated()
   }
 }
 
@@ -132,11 +170,72 @@ impl Display for Literal {
 
 // endregion
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+/// A set of literals backed by a bit set over literal indices, so that a literal and its negation
+/// are distinct members.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
 pub struct LiteralSet {
   set: UIntSet
 }
 
+impl LiteralSet {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn from_clause(clause: &LiteralVector) -> LiteralSet {
+    let mut literal_set = LiteralSet::new();
+    for &literal in clause.iter() {
+      literal_set.insert(literal);
+    }
+    literal_set
+  }
+
+  pub fn insert(&mut self, literal: Literal) {
+    self.set.insert(literal.index());
+  }
+
+  pub fn remove(&mut self, literal: Literal) {
+    self.set.remove(literal.index());
+  }
+
+  pub fn contains(&self, literal: Literal) -> bool {
+    self.set.contains(literal.index())
+  }
+
+  pub fn len(&self) -> usize {
+    self.set.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.set.is_empty()
+  }
+
+  pub fn clear(&mut self) {
+    self.set.clear();
+  }
+
+  pub fn intersect(&self, other: &LiteralSet) -> LiteralSet {
+    let mut set = self.set.clone();
+    set.intersect_with(&other.set);
+    LiteralSet { set }
+  }
+
+  pub fn union(&self, other: &LiteralSet) -> LiteralSet {
+    let mut set = self.set.clone();
+    set.union_with(&other.set);
+    LiteralSet { set }
+  }
+
+  /// The literals of the set in order of their index.
+  pub fn iter(&self) -> impl Iterator<Item = Literal> + '_ {
+    self.set.iter().map(Literal)
+  }
+
+  pub fn to_vector(&self) -> LiteralVector {
+    self.iter().collect()
+  }
+}
+
 /// Negates all literals in the vector in-place.
 pub fn negate_literals(literals: &mut LiteralVector) {
   for literal in literals {
@@ -144,7 +243,121 @@ pub fn negate_literals(literals: &mut LiteralVector) {
   }
 }
 
+/// Sorts the literals of a clause with `Literal::cmp_by_var`, so that the literals of each variable
+/// are adjacent when the clause is displayed or written as DIMACS.
+pub fn sort_clause_for_display(literals: &mut LiteralVector) {
+  literals.sort_by(Literal::cmp_by_var);
+}
+
+/// The literals of a clause sorted with duplicates removed, so that clauses with the same literals
+/// compare equal.
+pub fn canonical_clause(literals: &[Literal]) -> LiteralVector {
+  let mut canonical = literals.to_vec();
+  canonical.sort_unstable();
+  canonical.dedup();
+  canonical
+}
+
 /// Returns a string of the elements of the vector separated by spaces.
 pub fn display_literal_vector(literals: &LiteralVector) -> String {
   literals.join(" ")
 }
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn negate_in_place() {
+    let mut literal = Literal::new(3, false);
+    literal.negate();
+    assert_eq!(literal, Literal::new(3, true));
+    literal.negate();
+    assert_eq!(literal, Literal::new(3, false));
+  }
+
+  #[test]
+  fn negated_returns_flipped_copy() {
+    let literal = Literal::new(3, false);
+    assert_eq!(literal.negated(), Literal::new(3, true));
+    assert_eq!(literal.negated().negated(), literal);
+    assert_eq!(literal.negated(), !literal);
+  }
+
+  #[test]
+  fn strip_sign() {
+    for v in [0, 1, 7] {
+      let positive = Literal::positive(v);
+      let negative = Literal::negative(v);
+
+      assert_eq!(positive, Literal::new(v, false));
+      assert_eq!(negative, Literal::new(v, true));
+      assert!(!positive.sign());
+      assert!(negative.sign());
+
+      assert_eq!((!positive).unsign(), positive.unsign());
+      assert_eq!(negative.unsign(), positive);
+      assert!(!positive.unsign().sign());
+      assert!(!negative.unsign().sign());
+
+      assert_eq!(positive.var(), v);
+      assert_eq!(negative.var(), v);
+    }
+  }
+
+  #[test]
+  fn dimacs_conversion_round_trips() {
+    assert_eq!(Literal::from_dimacs(1), Literal::positive(0));
+    assert_eq!(Literal::from_dimacs(-3), Literal::negative(2));
+    for value in [1, -1, 2, -17] {
+      assert_eq!(Literal::from_dimacs(value).to_dimacs(), value);
+    }
+  }
+
+  #[test]
+  fn negate_literals_flips_every_literal() {
+    let mut literals = vec![Literal::new(0, false), Literal::new(1, true)];
+    negate_literals(&mut literals);
+    assert_eq!(literals, vec![Literal::new(0, true), Literal::new(1, false)]);
+  }
+
+  #[test]
+  fn sort_for_display_groups_by_variable() {
+    let mut clause = vec![
+      Literal::negative(4),
+      Literal::positive(1),
+      Literal::positive(4),
+      Literal::negative(0),
+      Literal::negative(1),
+    ];
+    sort_clause_for_display(&mut clause);
+    assert_eq!(
+      clause,
+      vec![
+        Literal::negative(0),
+        Literal::positive(1),
+        Literal::negative(1),
+        Literal::positive(4),
+        Literal::negative(4),
+      ]
+    );
+  }
+
+  #[test]
+  fn literal_set_algebra() {
+    let a = Literal::positive(0);
+    let b = Literal::positive(1);
+    let c = Literal::positive(2);
+    let left  = LiteralSet::from_clause(&vec![a, !b, c]);
+    let right = LiteralSet::from_clause(&vec![!a, !b, c]);
+
+    assert_eq!(left.intersect(&right).to_vector(), vec![!b, c]);
+    assert_eq!(left.union(&right).to_vector(), vec![a, !a, !b, c]);
+
+    let both = left.union(&right);
+    assert!(both.contains(a) && both.contains(!a));
+    assert!(!both.contains(b));
+    assert_eq!(both.len(), 4);
+  }
+}
@@ -13,6 +13,7 @@ use core::default::Default;
 use super::{
   LocalSearchMode
 };
+use crate::config::Config;
 
 #[cfg(test)]
 mod tests {
@@ -28,9 +29,10 @@ pub struct LocalSearchConfig {
   pub random_seed     : u32,
   pub best_known_value: i32,
   pub mode            : LocalSearchMode,
-  pub phase_sticky    : bool,
+  pub phase_sticky    : bool, // Initialize each solution from the biases rather than at random
   pub dbg_flips       : bool, // todo: Only define when in debug mode?
   pub itau            : f64,
+  pub reset_statistics: bool, // Reset the statistics at the start of every `check`
 }
 
 impl LocalSearchConfig {
@@ -55,14 +57,23 @@ impl LocalSearchConfig {
   pub fn best_known_value(&self) -> i32 {
     self.best_known_value
   }
+  pub fn reset_statistics(&self) -> bool {
+    self.reset_statistics
+  }
   pub fn set_random_seed(&mut self, random_seed: u32) {
     self.random_seed = random_seed;
   }
   pub fn set_best_known_value(&mut self, best_known_value: i32) {
     self.best_known_value = best_known_value;
   }
+  pub fn set_reset_statistics(&mut self, reset_statistics: bool) {
+    self.reset_statistics = reset_statistics;
+  }
+  pub fn set_phase_sticky(&mut self, phase_sticky: bool) {
+    self.phase_sticky = phase_sticky;
+  }
 
-  pub(crate) fn set_config(&mut self, cfg: &LocalSearchConfig) {
+  pub(crate) fn set_config(&mut self, cfg: &Config) {
     self.mode         = cfg.local_search_mode;
     self.random_seed  = cfg.random_seed;
     self.phase_sticky = cfg.phase_sticky;
@@ -79,6 +90,7 @@ impl Default for LocalSearchConfig {
       phase_sticky    : false,
       dbg_flips       : false,
       itau            : 0.5f64,
+      reset_statistics: false,
     }
   }
 }
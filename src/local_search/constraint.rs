@@ -1,9 +1,11 @@
 use crate::{Literal, LiteralVector};
 
+/// An at-most-k constraint: the sum of the coefficients of the true literals is at most `k`.
 #[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
 pub struct Constraint {
   pub(crate) id: usize,
   pub(crate) k: usize,
+  /// `k` minus the value of the constraint. The constraint is violated exactly when this is negative.
   pub(crate) slack: i64,
   pub(crate) literals: LiteralVector,
 }
@@ -16,7 +18,7 @@ impl Constraint{
       ..Self::default()
     }
   }
-  fn push(&mut self, literal: Literal) {
+  pub(crate) fn push(&mut self, literal: Literal) {
     self.literals.push(literal)
   }
   fn size(&self) -> usize {
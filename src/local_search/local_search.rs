@@ -11,11 +11,11 @@ anyway.
 
 use std::cell::RefCell;
 use std::cmp::min;
+use std::collections::HashSet;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 use itertools::Itertools;
-use num_traits::abs;
 
 use crate::{
   BoolVariable,
@@ -25,13 +25,14 @@ use crate::{
   LiftedBool,
   Literal,
   LiteralVector,
-  log::log_at_level,
+  log::{log_at_level, Verbosity},
   missing_types::{Parallel},
   Model,
   NULL_BOOL_VAR,
   ResourceLimit,
   Solver,
   Statistics,
+  watched::Watched,
 };
 // use crate::local_search::;
 
@@ -50,12 +51,13 @@ pub trait LocalSearchCore {
   fn add(&mut self, solver: &Solver);
   fn update_parameters(&mut self, p: ParametersRef);
   fn set_seed(&mut self, s: u32);
-  fn check(&self, assumptions: LiteralVector, parallel: &Parallel) -> LiftedBool;
+  fn check(&mut self, assumptions: &LiteralVector, parallel: RcRc<Parallel>) -> LiftedBool;
   fn reinit_with_solver(&mut self, solver: &Solver);
-  fn num_non_binary_clauses(&self) -> u32;
+  fn num_non_binary_clauses(&self) -> usize;
   fn resource_limit(&self) -> &ResourceLimit; // todo: probably use `Arc<ResourceLimit>`
   fn get_model(&self) -> &Model;
-  fn collect_statistics(&self, statistics: &Statistics);
+  fn collect_statistics(&self, statistics: &mut Statistics);
+  fn set_phase_sticky(&mut self, phase_sticky: bool);
   fn get_priority(&self, _bool_var: BoolVariable) -> f64  {
     return 0f64;
   }
@@ -147,8 +149,9 @@ impl LocalSearch {
 
   fn set_best_unsat(&mut self) {
     self.best_unsat = self.unsat_stack.len();
-    self.best_phase.reserve(self.vars.len());
-    for i in 0..self.vars.len() {
+    // Skip the sentinel variable.
+    self.best_phase.resize(self.num_vars(), false);
+    for i in 0..self.num_vars() {
       self.best_phase[i] = self.vars[i].value;
     }
   }
@@ -199,7 +202,7 @@ impl LocalSearch {
         | vi: &mut VariableInfo | vi.value = vi.bias > 50
       }
       else {
-        | vi: &mut VariableInfo | vi.value = (0 == (self.rand() % 2))
+        | vi: &mut VariableInfo | vi.value = if vi.fixed { vi.bias == 100 } else { 0 == (self.rand() % 2) }
       };
     self.vars
         .iter_mut()
@@ -270,16 +273,19 @@ impl LocalSearch {
     }
 
     if self.is_unsat {
-      log_at_level(0, "unsat during reinit\n");
+      log_at_level(Verbosity::Error, "unsat during reinit\n");
     }
     #[cfg(debug_assertions)]
-    verify_slack();
+    self.assert_slack_invariant();
   }
 
   fn init_cur_solution(&mut self) {
     for var_info in self.vars.iter_mut() {
       if !var_info.unit {
-        if self.config.phase_sticky() {
+        if var_info.fixed {
+          var_info.value = var_info.bias == 100;
+        }
+        else if self.config.phase_sticky() {
           var_info.value = ((self.rand() % 100) as u32) < var_info.bias;
         }
         else {
@@ -292,16 +298,11 @@ impl LocalSearch {
   fn init_slack(&mut self) {
     for v in 0..self.num_vars() {
       let is_true = self.cur_solution(v as BoolVariable);
-      let true_variable_coefficients =
-        match is_true {
-          false => &self.vars[v].watch.0,
-          true  => &self.vars[v].watch.1,
-        };
-      for pb_coefficient in true_variable_coefficients {
-        let constraint = self.constraints //[coeff.constraint_id];
-                                 .get_mut(pb_coefficient.constraint_id)
-                                 .unwrap();
-        constraint.slack -= pb_coefficient.coefficient;
+      // The coefficients of the literals of `v` that are currently true.
+      for i in 0..self.vars[v].watch[is_true].len() {
+        let pb_coefficient = self.vars[v].watch[is_true][i];
+        let constraint     = &mut self.constraints[pb_coefficient.constraint_id as usize];
+        constraint.slack  -= pb_coefficient.coefficient as i64;
       }
     }
     for c in 0..self.num_constraints() {
@@ -379,7 +380,7 @@ impl LocalSearch {
       self.propagate(!best);
     }
     else {
-      log_at_level(1, "(sat.local-search no best)\n");
+      log_at_level(Verbosity::Info, "(sat.local-search no best)\n");
     }
   }
 
@@ -408,7 +409,7 @@ impl LocalSearch {
 
         if c_next.is_none() {
           if c.k < self.constraint_value(&c) {
-            log_at_level(0, format!("unsat clause\n{}", self.format_constraint(&c)).as_str());
+            log_at_level(Verbosity::Error, format!("unsat clause\n{}", self.format_constraint(&c)).as_str());
             self.is_unsat = true;
             return;
           }
@@ -479,7 +480,7 @@ impl LocalSearch {
       }
 
       if best_var == NULL_BOOL_VAR {
-        log_at_level(1, "(sat.local_search :unsat)\n");
+        log_at_level(Verbosity::Info, "(sat.local_search :unsat)\n");
         return;
       }
 
@@ -496,7 +497,7 @@ impl LocalSearch {
         }
         self.add_unit(!lit, Literal::NULL);
         if !self.propagate(!lit) {
-          log_at_level(2, "unsat\n");
+          log_at_level(Verbosity::Debug, "unsat\n");
           self.is_unsat = true;
           return;
         }
@@ -515,50 +516,57 @@ impl LocalSearch {
     }
   }
 
+  /// Flips `flipvar` and updates the slack of every constraint it occurs in. Constraints are at-most-k
+  /// constraints, and `slack = k - value`, so a constraint is violated exactly when its slack is
+  /// negative. Literals that become true decrease the slack, and literals that become false increase
+  /// it.
   fn flip_walksat(&mut self, flipvar: BoolVariable) {
 
     self.stats.count_of_flips += 1;
     verify!(!self.is_unit(flipvar));
 
-    let flipvar_info    = &mut self.vars[flipvar];
-    flipvar_info.value  = !self.cur_solution(flipvar);
-    flipvar_info.flips += 1;
-
-    flipvar_info.slow_break.update(abs(flipvar_info.slack_score as f64));
+    let flip_is_true = !self.cur_solution(flipvar);
+    {
+      let flipvar_info    = &mut self.vars[flipvar];
+      flipvar_info.value  = flip_is_true;
+      flipvar_info.flips += 1;
 
-    let flip_is_true = self.cur_solution(flipvar);
-    let true_part    = flipvar_info.get_watch(flip_is_true);
-    let false_part   = flipvar_info.get_watch(!flip_is_true);
+      // `slow_break` tracks the magnitude of the slack score, which `walksat` turns into the break
+      // probabilities shared with other solvers. `unsigned_abs` also handles the sentinel's `i32::MIN`.
+      flipvar_info.slow_break.update(f64::from(flipvar_info.slack_score.unsigned_abs()));
+    }
 
-    for pb_constraint in true_part {
-      let constraint_id  = pb_constraint.constraint_id;
+    // Constraints in which a literal of `flipvar` became true.
+    for i in 0..self.vars[flipvar].watch[flip_is_true].len() {
+      let pb_coefficient = self.vars[flipvar].watch[flip_is_true][i];
+      let constraint_id  = pb_coefficient.constraint_id;
       let constraint     = &mut self.constraints[constraint_id as usize];
       let old_slack      = constraint.slack;
-      constraint.slack  -= pb_constraint.coeff;                           // Subtrace
-
-      #[cfg(feature = "debug")]
-      verify!(self.constraint_value(constraint) + constraint.slack == constraint.k);
+      constraint.slack  -= pb_coefficient.coefficient as i64;
 
       // When slack transitions from non-negative to negative, the constraint goes from sat to unsat.
-      if constraint.slack < 0 && old_slack >= 0 { // from non-negative to negative: sat -> unsat
+      if constraint.slack < 0 && old_slack >= 0 {
         self.unsat(constraint_id);
       }
     }
 
-    for pb_constraint in false_part {
-      let constraint_id  = pb_constraint.constraint_id;
+    // Constraints in which a literal of `flipvar` became false.
+    for i in 0..self.vars[flipvar].watch[!flip_is_true].len() {
+      let pb_coefficient = self.vars[flipvar].watch[!flip_is_true][i];
+      let constraint_id  = pb_coefficient.constraint_id;
       let constraint     = &mut self.constraints[constraint_id as usize];
       let old_slack      = constraint.slack;
-      constraint.slack  += pb_constraint.coeff;
+      constraint.slack  += pb_coefficient.coefficient as i64;
 
-      #[cfg(feature = "debug")]
-      verify!(self.constraint_value(constraint) + constraint.slack == constraint.k);
-
-      if constraint.slack < 0 && old_slack >= 0 { // from non-negative to negative: sat -> unsat
+      // When slack transitions from negative to non-negative, the constraint goes from unsat to sat.
+      if constraint.slack >= 0 && old_slack < 0 {
         self.sat(constraint_id);
       }
     }
 
+    #[cfg(debug_assertions)]
+    self.assert_slack_invariant();
+
     #[cfg(feature = "debug")]
     self.verify_unsat_stack();
   }
@@ -581,7 +589,7 @@ impl LocalSearch {
       }
     }
     if self.prop_queue.len() >= self.vars.len() {
-      log_at_level(0, "propagation loop\n");
+      log_at_level(Verbosity::Error, "propagation loop\n");
       return false;
     }
     if unit {
@@ -610,7 +618,7 @@ impl LocalSearch {
         elapsed_time
       };
       log_at_level(
-        1,
+        Verbosity::Info,
         format!(
           "(sat.local-search, :flips {} :noise {} :unsat {} :constraints {} :time {}\n",
           flips,
@@ -629,7 +637,7 @@ impl LocalSearch {
 
     self.reinit();
     #[cfg(feature = "debug")]
-    self.verify_slack();
+    self.assert_slack_invariant();
 
     // usage: timer.elapsed().as_secs();
     let timer = Instant::now();
@@ -641,7 +649,7 @@ impl LocalSearch {
       // Semantically different from z3 in that z3 always sets tries = 1, while here we allow tries == 0 if body
       // never runs.
       tries += 1;
-      self.stats.num_restarts += 1;
+      self.stats.count_of_restarts += 1;
       let mut step = 0u32;
 
       while step < self.max_steps && !self.unsat_stack.empty() {
@@ -738,7 +746,7 @@ impl LocalSearch {
   }
 
   fn verify_solution(&self) {
-    log_at_level(10, "verifying solution\n");
+    log_at_level(Verbosity::Trace, "verifying solution\n");
     for constraint in self.constraints{
       self.verify_constraint(&constraint)
     }
@@ -749,11 +757,11 @@ impl LocalSearch {
       let constraint = &self.constraints[i as usize];
       if constraint.k >= self.constraint_value(constraint) {
         log_at_level(
-          0,
+          Verbosity::Error,
           format!("{} {}\n", i, self.format_constraint(constraint)).as_str()
         );
         log_at_level(
-          0,
+          Verbosity::Error,
           format!("units {:?}", self.units.join(" ")).as_str()
         );
       }
@@ -763,11 +771,11 @@ impl LocalSearch {
 
   fn verify_constraint(&self, constraint: &Constraint) {
     let value = self.constraint_value(constraint);
-    log_at_level(11, &*format!("verify {}", c));
+    log_at_level(Verbosity::Trace, &*format!("verify {}", c));
     trace!("sat", &*format!("verify {}", c));
     if constraint.k < value {
       log_at_level(
-        0,
+        Verbosity::Error,
         format!(
           "violated constraint: {}value: {}",
           self.format_constraint(constraint),
@@ -778,11 +786,15 @@ impl LocalSearch {
   }
 
   fn verify_slack_with_constraint(&self, constraint: &Constraint) {
-    verify!(self.constraint_value(constraint) + constraint.slack == constraint.k);
+    verify!(self.constraint_value(constraint) as i64 + constraint.slack == constraint.k as i64);
   }
 
-  // inlined
-  // fn verify_slack(&self) {}
+  /// Verifies `constraint_value + slack == k` for every constraint. This is z3's `verify_slack`.
+  fn assert_slack_invariant(&self) {
+    for constraint in self.constraints.iter() {
+      self.verify_slack_with_constraint(constraint);
+    }
+  }
 
   fn verify_goodvar(&self) -> bool {
     let mut g = 0usize;
@@ -794,23 +806,27 @@ impl LocalSearch {
     return g == self.goodvar_stack.len();
   }
 
+  /// The sum of the coefficients of the true literals of `constraint`.
   fn constraint_value(&self, constraint: &Constraint) -> usize  {
     let mut value = 0usize;
-    for t in constraint {
-      if self.is_true(t) {
-        value += self.constraint_coeff(c, t);
+    for &t in constraint.iter() {
+      if self.is_true_literal(t) {
+        let coefficient = self.constraint_coefficient_with_literal(constraint, t);
+        debug_assert!(coefficient.is_some(), "literal {} is not watched by its constraint", t);
+        value += coefficient.unwrap_or(0) as usize;
       }
     }
     return value;
   }
 
-  fn constraint_coefficient_with_literal(&self, c: &Constraint, l: Literal) -> u32  {
-    for pb in self.vars[l.var()].get_watch(self.is_pos(l)) {
-      if pb.constraint_id == c.id as u32 {
-        return pb.coeff;
-      }
-    }
-    unreachable!();
+  /// The coefficient of `l` in `c`, or `None` if `l` does not occur in `c`.
+  fn constraint_coefficient_with_literal(&self, c: &Constraint, l: Literal) -> Option<u32>  {
+    self.vars
+        .get(l.var())?
+        .watch[self.is_pos(l)]
+        .iter()
+        .find(|pb| pb.constraint_id == c.id as u32)
+        .map(|pb| pb.coefficient)
   }
 
   fn print_info(&self) {
@@ -876,13 +892,33 @@ impl LocalSearch {
     return self.vars.len() - 1;
   }
 
+  /// Grows `self.vars` so that there are at least `variable_count` variables. This is what z3's
+  /// `vector::reserve` does, which is not what `Vec::reserve` does.
+  fn reserve_vars(&mut self, variable_count: usize) {
+    if self.vars.len() < variable_count {
+      self.vars.resize_with(variable_count, VariableInfo::default);
+    }
+  }
+
   /// Formats the `Constraints` and variables for printing out to the log (console by default).
+  /// Variables are numbered from 1 as in DIMACS if `dimacs`, and from 0 otherwise.
   /// The analog of `local_search::display(std::ostream& out)`.
-  fn format_constraints_and_vars(&self) -> String {
+  fn format_constraints_and_vars(&self, dimacs: bool) -> String {
     format!(
       "{}{}",
-      self.constraints.iter().map(self.format_constraint).join(""),
-      self.vars.iter().enumerate().map(|v, vi | vi.format(v)).join("")
+      self.constraints.iter().map(|constraint| self.format_constraint(constraint)).join(""),
+      self.vars
+          .iter()
+          .enumerate()
+          .map(
+            |(v, vi)|
+              if dimacs {
+                vi.format_dimacs(v as u32)
+              } else {
+                vi.format(v as u32)
+              }
+          )
+          .join("")
     )
   }
 
@@ -892,12 +928,10 @@ impl LocalSearch {
     let literals_list
         = constraint.iter()
                     .map(
-                      |literal| {
-                        let coeff = self.constraint_coefficient(constraint, literal);
-                        if coeff > 1 {
-                          format!("{} * {} ", coeff, literal)
-                        } else {
-                          format!("{} ", literal)
+                      |&literal| {
+                        match self.constraint_coefficient_with_literal(constraint, literal) {
+                          Some(coeff) if coeff > 1 => format!("{} * {} ", coeff, literal),
+                          _                        => format!("{} ", literal),
                         }
                       }
                     )
@@ -929,115 +963,25 @@ impl LocalSearch {
 
   // region public methods
 
-  pub fn rlimit(&self) -> &ResourceLimit  {
-    return &self.limit;
-  }
-
-  pub fn check(&mut self, assumptions: &LiteralVector, parallel: RcRc<Parallel>) -> LiftedBool  {
-    let mut old_parallel: RcRc<Parallel> = self.parallel.clone(); //Rc::new(RefCell::new(Parallel::default()));
-    self.parallel = parallel;
-
-    self.model.reset();
-    let num_units = self.units.len();
-    self.assumptions.reset();
-    self.assumptions.extend(assumptions);
-    self.init();
-
-    if self.is_unsat {
-      self.parallel = old_parallel;
-      return LiftedBool::False;
-    }
-
-    self.walksat();
-
-    trace!("sat", format!("{:?}\n", self.units));
-
-    // Remove unit clauses
-    for i in (num_units..self.units.len()).rev() {
-      self.vars[self.units[i]].unit = false;
-    }
-
-    self.units.truncate(num_units);
-
-    trace!("sat", {/* pass */});
-
-    let result= // The result of the following if-else block:
-      if self.is_unsat {
-        LiftedBool::False
-      }
-      else if self.unsat_stack.empty() {
-        self.verify_solution();
-        self.extract_model();
-        LiftedBool::True
-      }
-      else {
-        LiftedBool::Undefined
-      };
-
-    // Remove sentinel variable
-    self.vars.pop();
-
-    log_at_level(1, format!("(sat.local-search {})\n", result).as_str());
-    log_at_level(20, ""); // todo: What's the point?
-
-    return result;
-  }
-
-  pub fn num_non_binary_clauses(&self) -> usize  {
-    return self.num_non_binary_clauses;
-  }
-
-  pub fn add(&mut self, s: &Solver) {
-    self.import(s, false);
-  }
-
-  pub fn get_model(&self) -> &Model  {
-    return &self.model;
-  }
-
-  pub fn collect_statistics(&self, statistics: &Statistics) {
-    if self.config.dbg_flips() {
-      for (i, var_info) in self.vars.iter().enumerate() {
-        log_at_level(
-          0,
-          format!(
-            "flips: {} {} {}\n",
-            i,
-            var_info.flips,
-            var_info.slow_break
-          ).as_str()
-        );
-      }
-    }
-    statistics.update("local-search-flips", self.stats.count_of_flips);
-    statistics.update("local-search-restarts", self.stats.count_of_restarts);
-  }
-
-  pub fn update_params(&self, _parameters: ParametersRef) {
-    /* No parameters to update; pass. */
-  }
-
-  pub fn set_seed(&mut self, n: u32) {
-    self.config.set_random_seed(n);
-  }
-
-  pub fn reinit_with_solver(&mut self, solver: &Solver) {
-    self.import(solver, true);
-    if solver.best_phase_size > 0 {
-      for i in (0..self.num_vars()).rev() {
-        self.set_phase(i, solver.best_phase[i]);
-      }
-    }
-  }
-
   // Used by unit-walk
   pub fn set_phase(&mut self, v: BoolVariable, f: bool) {
     let mut variable = self.vars.get_mut(v).unwrap();
+    if variable.fixed { return; }
     if f  && variable.bias < 100 { variable.bias += 1; }
     if !f && variable.bias > 0   { variable.bias -= 1; }
   }
 
+  /// Fixes the phase of `v` to `value`: unlike `set_phase` and `set_bias`, which only nudge the
+  /// bias, every initial solution assigns `value` to `v`.
+  pub fn fix_phase(&mut self, v: BoolVariable, value: bool) {
+    let variable   = &mut self.vars[v];
+    variable.bias  = if value { 100 } else { 0 };
+    variable.fixed = true;
+    variable.value = value;
+  }
+
   pub fn set_bias(&mut self, v: BoolVariable, f: LiftedBool) {
+    if self.vars[v].fixed { return; }
     match f {
       LiftedBool::True => self.vars[v].bias = 99,
       LiftedBool::False => self.vars[v].bias = 1,
@@ -1049,12 +993,18 @@ impl LocalSearch {
     return self.best_phase[v];
   }
 
-  pub fn cur_solution(&self, v: BoolVariable) -> bool  {
-    return self.vars[v].value;
+  /// The assignment with the fewest unsatisfied constraints found by the last `check`, together with
+  /// that number. When `check` gives up with `LiftedBool::Undefined`, this is its best effort.
+  pub fn best_assignment(&self) -> (usize, Model) {
+    let mut model = Model::default();
+    for &value in self.best_phase.iter() {
+      model.push(LiftedBool::from(value));
+    }
+    (self.best_unsat, model)
   }
 
-  pub fn get_priority(&self, v: BoolVariable) -> f64  {
-    return self.vars[v].break_prob;
+  pub fn cur_solution(&self, v: BoolVariable) -> bool  {
+    return self.vars[v].value;
   }
 
   pub fn import(&mut self, s: &Solver, init: bool) -> Result<(), Error> {
@@ -1065,7 +1015,7 @@ impl LocalSearch {
     self.constraints.reset();
     self.units.reset();
     self.unsat_stack.reset();
-    self.vars.reserve(s.num_vars());
+    self.reserve_vars(s.number_of_variables() as usize);
     self.config.set_config(s.get_config());
 
     if self.config.phase_sticky() {
@@ -1081,30 +1031,26 @@ impl LocalSearch {
       self.add_clause(&singleton);
     }
 
-    // Copy binary clauses
-    {
-      let sz = s.watches.len();
-      for l_idx in 0..sz {
-        let l1 = !Literal(l_idx);
-        let wlist = s.watches.get(l_idx).unwrap();
-        for w in wlist {
-          if !w.is_binary_non_learned_clause() {
-            continue;
-          }
-          let l2 = w.get_literal();
-          if l1.index() > l2.index() {
+    // Copy binary clauses. The watch list of `l` holds the watches of clauses containing `!l`, and
+    // the binary clause (l1 l2) is in the watch lists of both `!l1` and `!l2`, so we copy it from
+    // the list of its smaller literal only. A binary clause added twice is copied once.
+    let mut binaries: HashSet<(Literal, Literal)> = HashSet::new();
+    for (l_idx, wlist) in s.watches.iter().enumerate() {
+      let l1 = !Literal(l_idx);
+      for w in wlist.list.iter() {
+        if let Watched::Binary { literal: l2, is_learned: false } = *w {
+          if l1.index() > l2.index() || !binaries.insert((l1, l2)) {
             continue;
           }
-          // todo: WRONG
-          ls = vec![l1, l2];
-          self.add_clause(ls);
+          let ls = vec![l1, l2];
+          self.add_clause(&ls);
         }
       }
     }
 
     // copy clauses
-    for clause in &s.clauses {
-      self.add_clause(clause.literals());
+    for &offset in &s.clauses {
+      self.add_clause(s.get_clause(offset).literals());
     }
     self.num_non_binary_clauses = s.clauses.len();
 
@@ -1141,6 +1087,12 @@ impl LocalSearch {
     Ok(())
   }
 
+  /// Zeroes the flip and restart counts. To measure individual runs, set
+  /// `LocalSearchConfig::reset_statistics` instead, which does this at the start of every `check`.
+  pub fn reset_statistics(&mut self) {
+    self.stats.reset();
+  }
+
   pub fn add_cardinality(&mut self, c: &LiteralVector, k: usize) {
     if k == 0 && c.len() == 1 {
       self.add_unit(c[0], Literal::NULL);
@@ -1148,12 +1100,13 @@ impl LocalSearch {
     }
 
     if k == 1 && c.len() == 2 {
-      log_at_level(0, format!("bin: {} + {} <= 1\n", !c[0], !c[1]).as_str());
+      log_at_level(Verbosity::Info, format!("bin: {} + {} <= 1\n", !c[0], !c[1]).as_str());
       for i in 0..2 {
         let (t, s) = (c[i], c[1-i]);
 
-        self.vars.reserve(t.var() + 1);
-        self.vars[t.var()].bin[self.is_pos(t)].push(s);
+        self.reserve_vars(t.var() + 1);
+        let is_pos = self.is_pos(t);
+        self.vars[t.var()].bin[is_pos].push(s);
       }
     }
 
@@ -1161,11 +1114,12 @@ impl LocalSearch {
     self.constraints.push(Constraint::new(k, id));
 
     for i in 0..c.len() {
-      self.vars.reserve(c[i].var() + 1);
-      let t = !c[i];
+      self.reserve_vars(c[i].var() + 1);
+      let t      = !c[i];
+      let is_pos = self.is_pos(t);
 
       self.vars[t.var()]
-          .watch[is_pos(t)]
+          .watch[is_pos]
           .push(
             PbCoefficient{
               constraint_id: id as u32,
@@ -1173,7 +1127,7 @@ impl LocalSearch {
             }
           );
 
-      self.constraints.last_mut().push(t);
+      self.constraints.last_mut().unwrap().push(t);
     }
 
   }
@@ -1185,19 +1139,20 @@ impl LocalSearch {
     }
     self.is_pb = true;
     let id = self.constraints.len();
-    self.constraints.push(constraint(k, id));
+    self.constraints.push(Constraint::new(k as usize, id));
     for i in 0..c.len() {
-      self.vars.reserve(c[i].var() + 1);
-      let t = c[i];
+      self.reserve_vars(c[i].var() + 1);
+      let t      = c[i];
+      let is_pos = self.is_pos(t);
       self.vars[t.var()]
-          .get_watch(self.is_pos(t))
+          .watch[is_pos]
           .push(
             PbCoefficient {
               constraint_id: id as u32,
               coefficient: coeffs[i]
             }
           );
-      self.constraints.last_mut().push(t);
+      self.constraints.last_mut().unwrap().push(t);
     }
   }
 
@@ -1205,16 +1160,354 @@ impl LocalSearch {
     return &self.config;
   }
 
+  pub fn config_mut(&mut self) -> &mut LocalSearchConfig {
+    &mut self.config
+  }
+
   // endregion public methods
 
 }
 
 
 
+impl LocalSearchCore for LocalSearch {
+  fn add(&mut self, s: &Solver) {
+    self.import(s, false);
+  }
+
+  fn update_parameters(&mut self, _parameters: ParametersRef) {
+    /* No parameters to update; pass. */
+  }
+
+  fn set_seed(&mut self, n: u32) {
+    self.config.set_random_seed(n);
+  }
+
+  fn set_phase_sticky(&mut self, phase_sticky: bool) {
+    self.config.set_phase_sticky(phase_sticky);
+  }
+
+  fn check(&mut self, assumptions: &LiteralVector, parallel: RcRc<Parallel>) -> LiftedBool  {
+    let mut old_parallel: RcRc<Parallel> = self.parallel.clone(); //Rc::new(RefCell::new(Parallel::default()));
+    self.parallel = parallel;
+
+    if self.config.reset_statistics() {
+      self.reset_statistics();
+    }
+    self.model.reset();
+    let num_units = self.units.len();
+    self.assumptions.reset();
+    self.assumptions.extend(assumptions);
+    self.init();
+
+    if self.is_unsat {
+      self.parallel = old_parallel;
+      return LiftedBool::False;
+    }
+
+    self.walksat();
+
+    trace!("sat", format!("{:?}\n", self.units));
+
+    // Remove unit clauses
+    for i in (num_units..self.units.len()).rev() {
+      self.vars[self.units[i]].unit = false;
+    }
+
+    self.units.truncate(num_units);
+
+    trace!("sat", {/* pass */});
+
+    let result= // The result of the following if-else block:
+      if self.is_unsat {
+        LiftedBool::False
+      }
+      else if self.unsat_stack.empty() {
+        self.verify_solution();
+        self.extract_model();
+        LiftedBool::True
+      }
+      else {
+        LiftedBool::Undefined
+      };
+
+    // Remove sentinel variable
+    self.vars.pop();
+
+    log_at_level(Verbosity::Info, format!("(sat.local-search {})\n", result).as_str());
+    log_at_level(Verbosity::Trace, ""); // todo: What's the point?
+
+    return result;
+  }
+
+  fn reinit_with_solver(&mut self, solver: &Solver) {
+    self.import(solver, true);
+    if solver.best_phase_size > 0 {
+      for i in (0..self.num_vars()).rev() {
+        self.set_phase(i, solver.best_phase[i]);
+      }
+    }
+  }
+
+  fn num_non_binary_clauses(&self) -> usize  {
+    return self.num_non_binary_clauses;
+  }
+
+  fn resource_limit(&self) -> &ResourceLimit  {
+    return &self.limit;
+  }
+
+  fn get_model(&self) -> &Model  {
+    return &self.model;
+  }
+
+  fn collect_statistics(&self, statistics: &mut Statistics) {
+    if self.config.dbg_flips() {
+      for (i, var_info) in self.vars.iter().enumerate() {
+        log_at_level(
+          Verbosity::Info,
+          format!(
+            "flips: {} {} {}\n",
+            i,
+            var_info.flips,
+            var_info.slow_break
+          ).as_str()
+        );
+      }
+    }
+    statistics.update("local-search-flips",    self.stats.count_of_flips);
+    statistics.update("local-search-restarts", self.stats.count_of_restarts);
+  }
+
+  fn get_priority(&self, v: BoolVariable) -> f64  {
+    return self.vars[v].break_prob;
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
+  use super::*;
+  use crate::data_structures::Statistic;
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  #[test]
+  fn collect_statistics_reports_flips() {
+    let mut local_search = LocalSearch::new();
+    local_search.stats.count_of_flips    = 7;
+    local_search.stats.count_of_restarts = 2;
+
+    let mut statistics = Statistics::new();
+    local_search.collect_statistics(&mut statistics);
+
+    assert_eq!(statistics["local-search-flips"],    Statistic::Integer(7));
+    assert_eq!(statistics["local-search-restarts"], Statistic::Integer(2));
+  }
+
+  #[test]
+  fn slack_invariant_holds_across_flips() {
+    let x = |v: BoolVariable| Literal::new(v, false);
+
+    let mut local_search = LocalSearch::new();
+    // At most one of x0, x1, x2. `add_cardinality` bounds the negations of its literals.
+    local_search.add_cardinality(&vec![!x(0), !x(1), !x(2)], 1);
+    // 2 x0 + 3 x2 <= 4
+    local_search.add_pb(&vec![x(0), x(2)], vec![2, 3], 4);
+
+    // Sentinel variable
+    local_search.vars.push(VariableInfo::default());
+    local_search.index_in_unsat_stack.resize(local_search.num_constraints(), 0);
+    for constraint in local_search.constraints.iter_mut() {
+      constraint.slack = constraint.k as i64;
+    }
+    for variable in local_search.vars.iter_mut() {
+      variable.value = false;
+    }
+    local_search.init_slack();
+    local_search.assert_slack_invariant();
+    assert!(local_search.unsat_stack.is_empty());
+
+    for &v in [0, 2, 1, 0, 2, 1, 1].iter() {
+      local_search.flip_walksat(v);
+      local_search.assert_slack_invariant();
+      // A constraint is on the unsat stack exactly when its slack is negative.
+      for constraint in local_search.constraints.iter() {
+        assert_eq!(
+          constraint.slack < 0,
+          local_search.unsat_stack.contains(&(constraint.id as u32))
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn flipping_high_slack_variable_raises_slow_break() {
+    let mut local_search = LocalSearch::new();
+    // A variable in no constraints, so flipping it leaves its slack score alone.
+    local_search.reserve_vars(1);
+    local_search.vars[0].slack_score = -6;
+    assert_eq!(local_search.vars[0].slow_break.mean(), 0.0);
+
+    let mut previous_mean = 0.0;
+    for _ in 0..5 {
+      local_search.flip_walksat(0);
+      let mean = local_search.vars[0].slow_break.mean();
+      assert!(mean > 0.0);
+      assert!(mean >= previous_mean && mean <= 6.0);
+      previous_mean = mean;
+    }
+  }
+
+  #[test]
+  fn coefficient_of_absent_literal_is_none() {
+    let x = |v: BoolVariable| Literal::new(v, false);
+
+    let mut local_search = LocalSearch::new();
+    // 2 x0 + 3 x2 <= 4
+    local_search.add_pb(&vec![x(0), x(2)], vec![2, 3], 4);
+    let constraint = local_search.constraints[0].clone();
+
+    assert_eq!(local_search.constraint_coefficient_with_literal(&constraint, x(0)), Some(2));
+    assert_eq!(local_search.constraint_coefficient_with_literal(&constraint, x(2)), Some(3));
+    assert_eq!(local_search.constraint_coefficient_with_literal(&constraint, x(1)), None);
+    assert_eq!(local_search.constraint_coefficient_with_literal(&constraint, !x(0)), None);
+    assert_eq!(local_search.constraint_coefficient_with_literal(&constraint, x(9)), None);
+  }
+
+  #[test]
+  fn local_search_core_trait_object() {
+    use crate::status::Status;
+
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..5).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0], x[1], x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![!x[1], x[2], !x[3], x[4]], Status::input());
+    solver.mk_clause_core(&vec![x[0], !x[4]], Status::input());
+
+    let mut local_search: Box<dyn LocalSearchCore> = Box::new(LocalSearch::new());
+    local_search.add(&solver);
+    assert_eq!(local_search.num_non_binary_clauses(), 2);
+  }
+
+  #[test]
+  fn check_resets_statistics_when_requested() {
+    let x = |v: BoolVariable| Literal::new(v, false);
+    let no_parallel = || Rc::new(RefCell::new(Parallel::default()));
+
+    let mut local_search = LocalSearch::new();
+    local_search.add_cardinality(&vec![!x(0), !x(1), !x(2)], 1);
+
+    // Without a reset, flips accumulate on top of the previous count.
+    local_search.stats.count_of_flips = 1_000_000;
+    local_search.check(&LiteralVector::new(), no_parallel());
+    assert!(local_search.stats.count_of_flips >= 1_000_000);
+
+    local_search.config_mut().set_reset_statistics(true);
+    local_search.check(&LiteralVector::new(), no_parallel());
+    assert!(local_search.stats.count_of_flips < 1_000_000);
+  }
+
+  #[test]
+  fn import_copies_binary_clauses() {
+    use crate::status::Status;
+
+    let mut solver = Solver::default();
+    let x0 = Literal::new(solver.mk_var(true, true), false);
+    let x1 = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![x0, !x1], Status::input());
+
+    let mut local_search = LocalSearch::new();
+    local_search.import(&solver, false).unwrap();
+
+    // The clause (x0 !x1) becomes !x0 + x1 <= 1.
+    assert_eq!(local_search.num_constraints(), 1);
+    let constraint = &local_search.constraints[0];
+    assert_eq!(constraint.k, 1);
+    let mut literals = constraint.literals.clone();
+    literals.sort();
+    assert_eq!(literals, vec![!x0, x1]);
+    assert_eq!(local_search.num_non_binary_clauses(), 0);
+  }
+
+  #[test]
+  fn import_skips_duplicate_binary_clauses() {
+    use crate::status::Status;
+
+    let mut solver = Solver::default();
+    let x0 = Literal::new(solver.mk_var(true, true), false);
+    let x1 = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![x0, !x1], Status::input());
+    solver.mk_clause_core(&vec![!x1, x0], Status::input());
+
+    let mut local_search = LocalSearch::new();
+    local_search.import(&solver, false).unwrap();
+    assert_eq!(local_search.num_constraints(), 1);
+  }
+
+  #[test]
+  fn fixed_phase_survives_reinitialization() {
+    let x = |v: BoolVariable| Literal::new(v, false);
+
+    let mut local_search = LocalSearch::new();
+    local_search.add_cardinality(&vec![!x(0), !x(1), !x(2)], 2);
+    local_search.fix_phase(1, false);
+    local_search.set_phase(1, true);
+    local_search.set_bias(1, LiftedBool::True);
+
+    for _ in 0..5 {
+      local_search.init();
+      assert!(!local_search.cur_solution(1));
+      local_search.init_cur_solution();
+      assert!(!local_search.cur_solution(1));
+    }
+  }
+
+  #[test]
+  fn phase_sticky_initializes_from_saved_phases() {
+    let mut solver = Solver::default();
+    let variables: Vec<BoolVariable> = (0..64).map(|_| solver.mk_var(true, true)).collect();
+    for &v in variables.iter() {
+      solver.set_phase_hint(v, false);
+    }
+
+    solver.set_phase_sticky(true);
+    let mut local_search = LocalSearch::new();
+    local_search.import(&solver, false).unwrap();
+    local_search.init();
+    assert!(variables.iter().all(|&v| !local_search.cur_solution(v)));
+
+    // Without sticky phases the biases are ignored, and some of the 64 variables start true.
+    solver.set_phase_sticky(false);
+    let mut local_search = LocalSearch::new();
+    local_search.import(&solver, false).unwrap();
+    local_search.init();
+    assert!(variables.iter().any(|&v| local_search.cur_solution(v)));
+  }
+
+  #[test]
+  fn best_assignment_survives_giving_up() {
+    let x = |v: BoolVariable| Literal::new(v, false);
+    let no_parallel = || Rc::new(RefCell::new(Parallel::default()));
+
+    // Every assignment of x0 and x1 violates exactly one of these clauses.
+    let mut local_search = LocalSearch::new();
+    local_search.add_clause(&vec![x(0), x(1)]);
+    local_search.add_clause(&vec![x(0), !x(1)]);
+    local_search.add_clause(&vec![!x(0), x(1)]);
+    local_search.add_clause(&vec![!x(0), !x(1)]);
+    local_search.max_steps = 50;
+    local_search.limit.push(3);
+
+    assert_eq!(local_search.check(&LiteralVector::new(), no_parallel()), LiftedBool::Undefined);
+
+    let (unsat_count, model) = local_search.best_assignment();
+    assert_eq!(unsat_count, local_search.best_unsat);
+    assert_eq!(unsat_count, 1);
+    assert_ne!(model[0], LiftedBool::Undefined);
+    assert_ne!(model[1], LiftedBool::Undefined);
+  }
 }
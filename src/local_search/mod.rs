@@ -28,6 +28,7 @@ pub use local_search::{
   LocalSearchCore,
   LocalSearch
 };
+pub(crate) use constraint::Constraint;
 
 
 
@@ -23,6 +23,7 @@ pub struct VariableInfo {
   pub(crate) break_prob      : f64,
   pub(crate) conf_change     : bool,    // Whether its configure changed since its last flip
   pub(crate) explain         : Literal, // Explanation for unit assignment
+  pub(crate) fixed           : bool,    // Whether the bias is hard, so that the value always follows it
   pub(crate) flips           : u32,
   pub(crate) in_goodvar_stack: bool,
   pub(crate) neighbors       : BoolVariableVector,           // neighborhood variables
@@ -42,6 +43,7 @@ impl Default for VariableInfo {
       bias : 50u32,
       unit            : false,
       explain         : Literal(0),
+      fixed           : false,
       conf_change     : true,
       in_goodvar_stack: false,
       score           : 0,
@@ -58,7 +60,17 @@ impl Default for VariableInfo {
 }
 
 impl VariableInfo {
+  /// Formats the variable `v` using its internal 0-based index.
   pub fn format(&self, v: u32) -> String {
+    self.format_as(v)
+  }
+
+  /// Formats the variable `v` using its 1-based DIMACS index.
+  pub fn format_dimacs(&self, v: u32) -> String {
+    self.format_as(v + 1)
+  }
+
+  fn format_as(&self, v: u32) -> String {
     let truth =  // the following if block:
         if self.value {
           "true"
@@ -78,8 +90,17 @@ impl VariableInfo {
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  #[test]
+  fn dimacs_format_is_one_based() {
+    let info = VariableInfo::default();
+    assert!(info.format(0).starts_with("v0 := "));
+    assert!(info.format_dimacs(0).starts_with("v1 := "));
+  }
 }
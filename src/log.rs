@@ -140,35 +140,83 @@ pub(crate) mod trace {
 
 // Global control over verbose messaging.
 pub(crate) mod verbosity {
-  use std::io::{Stdout, stdout, Write};
+  use std::io::{stdout, Write};
+  use std::sync::Mutex;
+  use std::sync::atomic::{AtomicI32, Ordering};
 
-  // todo: Make `VERBOSITY` an enum. Discriminants must be numerically compatible with Z3.
-  // todo: Put `VERBOSITY` behind a mutex to get rid of `unsafe` and make thread safe.
-  pub(crate) static mut VERBOSITY     : i32    = 0;
-  pub(crate) static mut VERBOSE_STREAM: Stdout = stdout();
+  /// How much the library reports. Each level includes the messages of the levels before it. The
+  /// discriminants are numerically compatible with z3's integer verbosity.
+  #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash)]
+  #[repr(i32)]
+  pub enum Verbosity {
+    Silent = 0,
+    Error  = 1,
+    Warn   = 2,
+    Info   = 3,
+    Debug  = 4,
+    Trace  = 5,
+  }
 
-  fn verbosity_is_at_least(lvl: i32) -> bool{
-    // Mutable static variables require `unsafe`, as they are not thread safe.
-    unsafe{
-      lvl >= VERBOSITY
+  impl From<i32> for Verbosity {
+    /// Levels above `Trace` are clamped to `Trace`, and negative levels to `Silent`.
+    fn from(level: i32) -> Self {
+      match level {
+        i32::MIN..=0 => Verbosity::Silent,
+        1            => Verbosity::Error,
+        2            => Verbosity::Warn,
+        3            => Verbosity::Info,
+        4            => Verbosity::Debug,
+        _            => Verbosity::Trace,
+      }
     }
   }
 
-  pub fn set_verbosity(new_value: i32) {
-    unsafe {
-      VERBOSITY = new_value;
-    }
+  static VERBOSITY     : AtomicI32                         = AtomicI32::new(Verbosity::Silent as i32);
+  // `None` means `stdout`.
+  static VERBOSE_STREAM: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+  pub fn verbosity() -> Verbosity {
+    Verbosity::from(VERBOSITY.load(Ordering::Relaxed))
+  }
+
+  pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as i32, Ordering::Relaxed);
+  }
+
+  /// Sets the verbosity from a z3-style integer level.
+  pub fn set_verbosity_level(level: i32) {
+    set_verbosity(Verbosity::from(level));
+  }
+
+  /// Sends verbose messages to `stream` instead of `stdout`.
+  pub fn set_verbose_stream(stream: Box<dyn Write + Send>) {
+    *VERBOSE_STREAM.lock().unwrap() = Some(stream);
+  }
+
+  /// Sends verbose messages to `stdout` again.
+  pub fn reset_verbose_stream() {
+    take_verbose_stream();
+  }
+
+  /// Sends verbose messages to `stdout` again, returning the stream they went to before, if any.
+  pub(crate) fn take_verbose_stream() -> Option<Box<dyn Write + Send>> {
+    VERBOSE_STREAM.lock().unwrap().take()
+  }
+
+  fn verbosity_is_at_least(level: Verbosity) -> bool {
+    level != Verbosity::Silent && verbosity() >= level
   }
 
   pub(crate) fn verbose_emit(msg: &str) {
-    unsafe{
-      VERBOSE_STREAM.write(msg.as_bytes())?;
-    }
+    // Verbose output is best effort; a failing stream must not abort the solver.
+    let _ = match VERBOSE_STREAM.lock().unwrap().as_mut() {
+      Some(stream) => stream.write_all(msg.as_bytes()),
+      None         => stdout().write_all(msg.as_bytes()),
+    };
   }
 
-  /// Equivalent to z3's `CASSERT`.
-  // todo: Actually, `CASSERT` only runs if assertions are enabled and invokes the debugger.
-  pub(crate) fn log_at_level(level: i32, msg: &str){
+  /// Emits `msg` if the verbosity is at least `level`. Equivalent to z3's `IF_VERBOSE`.
+  pub(crate) fn log_at_level(level: Verbosity, msg: &str){
     if verbosity_is_at_least(level){
       verbose_emit(msg);
     }
@@ -178,8 +226,58 @@ pub(crate) mod verbosity {
 
 #[cfg(test)]
 mod tests {
+  use std::io::Write;
+  use std::sync::{Arc, Mutex};
+
+  use super::*;
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  /// A verbose stream that keeps what is written to it.
+  #[derive(Clone, Default)]
+  struct CapturedSink(Arc<Mutex<Vec<u8>>>);
+
+  impl Write for CapturedSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+      self.0.lock().unwrap().extend_from_slice(buf);
+      Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn messages_above_verbosity_are_suppressed() {
+    // The verbosity and the stream are global, so put back whatever was in place before.
+    let previous_verbosity = verbosity();
+    let previous_stream    = take_verbose_stream();
+
+    let sink = CapturedSink::default();
+    set_verbose_stream(Box::new(sink.clone()));
+    set_verbosity(Verbosity::Info);
+
+    log_at_level(Verbosity::Error, "<error message>");
+    log_at_level(Verbosity::Info,  "<info message>");
+    log_at_level(Verbosity::Debug, "<debug message>");
+    log_at_level(Verbosity::Trace, "<trace message>");
+
+    set_verbosity(previous_verbosity);
+    match previous_stream {
+      Some(stream) => set_verbose_stream(stream),
+      None         => reset_verbose_stream(),
+    }
+
+    let captured = String::from_utf8(sink.0.lock().unwrap().clone()).unwrap();
+    assert!(captured.contains("<error message>"));
+    assert!(captured.contains("<info message>"));
+    assert!(!captured.contains("<debug message>"));
+    assert!(!captured.contains("<trace message>"));
+    assert_eq!(Verbosity::from(10), Verbosity::Trace);
+    assert_eq!(Verbosity::Warn as i32, 2);
+  }
 }
@@ -28,7 +28,6 @@ switch (eh.caller_id()) {
 pub type ASTManager = ();
 pub type AsymmBranch = ();
 pub type BinarySPR = ();
-pub type ClauseAllocator = ();
 // A priority queue
 pub type Cleaner = ();
 pub type Cuber = ();
@@ -39,9 +38,7 @@ pub type Expression = ();
 pub type ExpressionVector
   = Vec<Rc<Expression>>;
 pub type Extension = ();
-pub type Justification = ();
 pub type ModelConverter = ();
-pub type MinimalUnsatisfiableSet = (); //MUS
 /// Binary Set-Propagation-Redundant Clauses
 pub type Parallel = ();
 pub type Probing = ();
@@ -50,8 +47,6 @@ pub type SCC = ();
 pub type ScopedLimitTrail = ();
 pub type SearchState = ();
 pub type Simplifier = ();
-pub type Stopwatch = ();
-pub type VariableQueue = ();
 
 
 /*
@@ -6,35 +6,37 @@
 */
 
 use crate::{
+  clause::Clause,
   LiftedBool,
   BoolVariable,
   Literal
 };
 use std::fmt::{Formatter, Display};
-use std::ops::{Index, Not};
+use std::ops::Index;
 use std::borrow::Borrow;
-use itertools::Itertools;
 
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
 pub struct Model {
   assignments: Vec<LiftedBool>
 }
 
+/// Renders the model like the body of a DIMACS `v` line: the 1-based index of each true variable and
+/// the negated index of each false one. Undefined variables are left out.
 impl Display for Model {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    let stringified: String = self.assignments
-                          .iter()
-                          .enumerate()
-                          .filter(| (i, lb) | lb != LiftedBool::Undefined)
-                          .map(| (i, lb) |
-                            if lb == LiftedBool::True {
-                              format!("{}", i)
-                            } else {
-                              format!("-{}", i)
-                            }
-                          )
-                          .collect()
-                          .join(" ");
-    write!(f, "{}", stringified)
+    let values: Vec<String> = self.assignments
+                                  .iter()
+                                  .enumerate()
+                                  .filter(|(_, &value)| value != LiftedBool::Undefined)
+                                  .map(|(v, &value)|
+                                    if value == LiftedBool::True {
+                                      format!("{}", v + 1)
+                                    } else {
+                                      format!("-{}", v + 1)
+                                    }
+                                  )
+                                  .collect();
+    write!(f, "{}", values.join(" "))
   }
 }
 
@@ -46,19 +48,6 @@ impl Index<BoolVariable> for Model{
   }
 }
 
-impl Index<Literal> for Model{
-  type Output = LiftedBool;
-
-  fn index(&self, index: Literal) -> &Self::Output {
-    let mut val = self[index.var()];
-    if index.sign() {
-      &val
-    } else {
-      &val.not()
-    }
-  }
-}
-
 impl Model {
 
   pub fn clear(&mut self){
@@ -69,16 +58,86 @@ impl Model {
     self.assignments.push(value);
   }
 
+  /// The number of variables the model covers, including undefined ones.
+  pub fn len(&self) -> usize {
+    self.assignments.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.assignments.is_empty()
+  }
+
+  /// The variables the model assigns, with their values, in increasing order. Undefined variables
+  /// are skipped.
+  pub fn iter(&self) -> impl Iterator<Item = (BoolVariable, LiftedBool)> + '_ {
+    self.assignments
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| value != LiftedBool::Undefined)
+        .map(|(v, &value)| (v, value))
+  }
+
+  /// Renders the model as a JSON object mapping each assigned variable to its value, for instance
+  /// `{"1": true, "2": false}`. Undefined variables are skipped. With `one_indexed`, variables are
+  /// numbered from 1 as in DIMACS; otherwise by their `BoolVariable` index.
+  pub fn to_json(&self, one_indexed: bool) -> String {
+    let offset  = if one_indexed { 1 } else { 0 };
+    let entries = self.iter()
+                      .map(|(v, value)| format!("\"{}\": {}", v + offset, value == LiftedBool::True))
+                      .collect::<Vec<String>>();
+    format!("{{{}}}", entries.join(", "))
+  }
+
+  /// The value of `var`, or `None` if the model does not cover it.
+  pub fn get(&self, var: BoolVariable) -> Option<LiftedBool> {
+    self.assignments.get(var).copied()
+  }
+
+  /// The value of `literal`, which is the negation of the value of its variable if the literal is
+  /// negative. Variables the model does not cover are undefined. `Index` can't be implemented for
+  /// `Literal`, as the negated value is not stored anywhere to return a reference to.
+  pub fn literal_value(&self, literal: Literal) -> LiftedBool {
+    match self.get(literal.var()) {
+      Some(_) => value_of_literal(literal, self),
+      None    => LiftedBool::Undefined,
+    }
+  }
+
+  /// The value of `literal`. Variables the model does not cover are undefined.
+  pub fn satisfies_literal(&self, literal: Literal) -> LiftedBool {
+    self.literal_value(literal)
+  }
+
+  /// Whether some literal of `clause` is true. Undefined literals count as not satisfying it.
+  pub fn satisfies_clause(&self, clause: &Clause) -> bool {
+    clause.literals().iter().any(|&literal| self.satisfies_literal(literal) == LiftedBool::True)
+  }
+
+  /// A copy of the model in which every variable `v` with `external[v]` false is undefined, so that
+  /// variables introduced internally, for instance by encodings, are not reported. Variables keep
+  /// their indices.
+  pub fn external_only(&self, external: &[bool]) -> Model {
+    let assignments = self.assignments
+                          .iter()
+                          .enumerate()
+                          .map(|(v, &value)| match external.get(v) {
+                            Some(true) => value,
+                            _          => LiftedBool::Undefined,
+                          })
+                          .collect();
+    Model { assignments }
+  }
+
 }
 
 pub fn value_of_bool_variable(var: BoolVariable, model: &Model) -> LiftedBool {
-  model[var.into()]
+  model[var]
 }
 
 pub fn value_of_literal(literal: Literal, model: &Model) -> LiftedBool {
-  let result = model[literal.var().into()];
+  let result = model[literal.var()];
   match literal.sign() {
-    true  => -result,
+    true  => !result,
     false => result
   }
 }
@@ -88,8 +147,70 @@ pub fn value_of_literal(literal: Literal, model: &Model) -> LiftedBool {
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+
+  #[test]
+  fn clauses_are_evaluated_conservatively() {
+    let mut model = Model::default();
+    model.push(LiftedBool::True);
+    model.push(LiftedBool::False);
+    model.push(LiftedBool::Undefined);
+    let (a, b, c) = (Literal::positive(0), Literal::positive(1), Literal::positive(2));
+
+    assert_eq!(model.satisfies_literal(a), LiftedBool::True);
+    assert_eq!(model.satisfies_literal(!b), LiftedBool::True);
+    assert_eq!(model.satisfies_literal(c), LiftedBool::Undefined);
+    assert_eq!(model.satisfies_literal(Literal::positive(7)), LiftedBool::Undefined);
+
+    assert!(model.satisfies_clause(&Clause::new(0, vec![!a, !b], false)));
+    assert!(!model.satisfies_clause(&Clause::new(1, vec![!a, b, c, !c], false)));
+  }
+
+  #[test]
+  fn literal_values_follow_sign() {
+    let mut model = Model::default();
+    model.push(LiftedBool::True);
+    model.push(LiftedBool::False);
+    model.push(LiftedBool::Undefined);
+
+    assert_eq!(model.literal_value(Literal::positive(0)), LiftedBool::True);
+    assert_eq!(model.literal_value(Literal::negative(0)), LiftedBool::False);
+    assert_eq!(model.literal_value(Literal::positive(1)), LiftedBool::False);
+    assert_eq!(model.literal_value(Literal::negative(1)), LiftedBool::True);
+    assert_eq!(model.literal_value(Literal::positive(2)), LiftedBool::Undefined);
+    assert_eq!(model.literal_value(Literal::negative(2)), LiftedBool::Undefined);
+  }
+
   #[test]
-  fn it_works() {
-    assert_eq!(2 + 2, 4);
+  fn displays_as_dimacs_values() {
+    let mut model = Model::default();
+    for value in [LiftedBool::True, LiftedBool::False, LiftedBool::True, LiftedBool::Undefined] {
+      model.push(value);
+    }
+    assert_eq!(model.to_string(), "1 -2 3");
+  }
+
+  #[test]
+  fn iter_skips_undefined_variables() {
+    let mut model = Model::default();
+    assert!(model.is_empty());
+    for value in [LiftedBool::False, LiftedBool::Undefined, LiftedBool::True] {
+      model.push(value);
+    }
+
+    assert_eq!(model.len(), 3);
+    assert_eq!(model.iter().collect::<Vec<_>>(), vec![(0, LiftedBool::False), (2, LiftedBool::True)]);
+  }
+
+  #[test]
+  fn json_skips_undefined_variables() {
+    let mut model = Model::default();
+    assert_eq!(model.to_json(true), "{}");
+    for value in [LiftedBool::True, LiftedBool::Undefined, LiftedBool::False] {
+      model.push(value);
+    }
+
+    assert_eq!(model.to_json(true), r#"{"1": true, "3": false}"#);
+    assert_eq!(model.to_json(false), r#"{"0": true, "2": false}"#);
   }
 }
@@ -0,0 +1,58 @@
+/*!
+
+Deletion-based extraction of a minimal unsatisfiable subset (MUS) of the assumptions. Starting from
+the core of a `check` that returned `LiftedBool::False`, each assumption is dropped in turn and the
+rest are checked again. If they are still unsatisfiable, the assumption is not needed, and the core
+of that check, which may be smaller still, replaces the candidate. Otherwise the assumption is kept.
+The result is minimal: dropping any one of its assumptions makes the clauses satisfiable.
+
+Each step is a full `check`, so extraction costs up to one check per literal of the core.
+
+*/
+
+use crate::{
+  LiftedBool,
+  LiteralVector,
+  Solver,
+};
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct MinimalUnsatisfiableSet {
+  checks: u32, // The number of checks done by all extractions
+}
+
+impl MinimalUnsatisfiableSet {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn checks(&self) -> u32 {
+    self.checks
+  }
+
+  /// Shrinks `core`, an unsatisfiable set of assumptions, to a minimal one. Returns the shrunken
+  /// core and whether it is known to be minimal, which it is not if a check was interrupted.
+  pub fn minimize(&mut self, solver: &mut Solver, core: &LiteralVector) -> (LiteralVector, bool) {
+    let mut mus = core.clone();
+    let mut i   = 0;
+
+    while i < mus.len() {
+      let mut candidate = mus.clone();
+      candidate.remove(i);
+      self.checks += 1;
+
+      match solver.check(&candidate) {
+        LiftedBool::False => {
+          // The new core is a subset of `candidate`. Keep the order of `mus` so that the
+          // assumptions before position `i` stay known to be necessary.
+          let new_core = solver.core();
+          mus = candidate.into_iter().filter(|literal| new_core.contains(literal)).collect();
+        }
+        LiftedBool::True      => i += 1,
+        LiftedBool::Undefined => return (mus, false),
+      }
+    }
+
+    (mus, true)
+  }
+}
@@ -0,0 +1,27 @@
+/*!
+
+A `SolverObserver` is told about the main events of the search: decisions, conflicts, restarts, and
+backtracks. It is meant for instrumentation such as tracing and visualization. Unlike a
+`BranchingStrategy`, an observer cannot see or influence the solver, so it costs nothing when none is
+set.
+
+*/
+
+use crate::{
+  Literal,
+  LiteralVector,
+};
+
+pub trait SolverObserver {
+  /// Called after `literal` is decided, opening decision level `level`.
+  fn on_decision(&mut self, _literal: Literal, _level: u32) {}
+
+  /// Called after each conflict with the learned clause.
+  fn on_conflict(&mut self, _lemma: &LiteralVector) {}
+
+  /// Called after each restart, once the solver has backtracked to the base level.
+  fn on_restart(&mut self) {}
+
+  /// Called after every backtrack with the decision level backtracked to.
+  fn on_backtrack(&mut self, _to_level: u32) {}
+}
@@ -6,18 +6,24 @@
 
 
 use std::{
+  cell::RefCell,
   collections::HashSet,
-  sync::Mutex, rc::Rc
+  sync::{Arc, Mutex, RwLock}, rc::Rc
 };
 
 use crate::{
   parameters::ParameterValue,
+  LiftedBool,
   Literal,
   LiteralVector,
+  ResourceLimit,
   Solver,
+  Statistic,
+  Statistics,
   clause::Clause,
+  config::Config,
   log_assert,
-  log::log_at_level,
+  log::{log_at_level, Verbosity},
   resource_limit::ArcRwResourceLimit, status::Status
 };
 
@@ -58,7 +64,7 @@ impl VectorPool {
   }
 
   pub fn add_vector(&mut self, owner: VectorIndex, vector: &Vec<VectorIndex>) {
-    self.vectors.push(vector);
+    self.vectors.push(vector.clone());
     self.owners.push(owner);
   }
 
@@ -101,12 +107,16 @@ pub struct Parallel<'a, 'b> {
 impl<'a, 'b> Parallel<'a, 'b> {
 
   // Todo: Make this take a resource limit, not a solver
-  pub fn new(&self, solver: &Solver) -> Self {
+  pub fn new(solver: &Solver) -> Self {
+    let num_threads = solver.get_config().num_threads as usize;
+    let mut pool    = VectorPool::default();
+    pool.reserve(num_threads);
+
     Parallel {
       units    : LiteralVector::new(),
       unit_set : VectorIndexSet::new(),
       literals : LiteralVector::new(),
-      pool_lock: Mutex::new(VectorPool::default()),
+      pool_lock: Mutex::new(pool),
 
       // For exchange with local search:
       num_clauses   : 0,
@@ -115,7 +125,7 @@ impl<'a, 'b> Parallel<'a, 'b> {
       priorities    : Vec::new(),
 
       resource_limit: solver.resource_limit.clone(),
-      limits        : Vec::new(),
+      limits        : Vec::with_capacity(num_threads.saturating_sub(1)),
       solvers       : Vec::new() // Vector of solver pointers, might need to be Rcs
     }
   }
@@ -125,8 +135,10 @@ impl<'a, 'b> Parallel<'a, 'b> {
   /// Plingeling heuristic:
   /// https://epub.jku.at/obvulioa/content/titleinfo/5973528/full.pdf
   /// http://fmv.jku.at/papers/Biere-SAT-Competition-2013-Lingeling.pdf
-  fn enable_add(c: &Clause) -> bool {
-    return (c.size() <= 40 && c.glue() <= 8) || c.glue() <= 2;
+  /// The thresholds are `Config::share_max_size`, `share_max_glue`, and `share_always_glue`.
+  pub(crate) fn enable_add(config: &Config, c: &Clause) -> bool {
+    return (c.size() <= config.share_max_size && c.glue() <= config.share_max_glue)
+        || c.glue() <= config.share_always_glue;
   }
 
   pub fn init_solvers(&mut self, solver: &mut Solver, num_extra_solvers: usize){
@@ -155,6 +167,74 @@ impl<'a, 'b> Parallel<'a, 'b> {
     solver.parameters["phase"] = saved_phase;
   }
 
+  /// Runs a portfolio of `num_workers` copies of `main` on the current thread. In a fixed order, each
+  /// worker searches for `steps_per_turn` resource limit steps, and at the end of every turn the clauses
+  /// the workers learned are handed to the others. Nothing depends on thread scheduling, so two runs
+  /// with the same random seed behave identically. This makes portfolio behavior reproducible when
+  /// debugging.
+  ///
+  /// Afterward the workers are kept in `self.solvers` so that their statistics can be inspected.
+  pub fn run_deterministic(&mut self, main: &mut Solver<'b>, num_workers: usize, steps_per_turn: u32) -> LiftedBool {
+    log_assert!(num_workers > 0);
+
+    let mut workers : Vec<Solver<'b>>                       = Vec::with_capacity(num_workers);
+    let mut outboxes: Vec<Rc<RefCell<Vec<LiteralVector>>>> = Vec::with_capacity(num_workers);
+
+    for i in 0..num_workers {
+      let limit: ArcRwResourceLimit = Arc::new(RwLock::new(ResourceLimit::new()));
+      main.resource_limit.write().unwrap().push_child(limit.clone());
+
+      let mut worker = Solver::from_params_limit(main.parameters.clone(), limit);
+      worker.copy(main, true);
+      worker.set_random_seed(main.get_config().random_seed.wrapping_add(i as u32));
+
+      let outbox = Rc::new(RefCell::new(Vec::new()));
+      let sink   = outbox.clone();
+      worker.set_learned_callback(Box::new(move |lemma: &LiteralVector| sink.borrow_mut().push(lemma.clone())));
+
+      workers.push(worker);
+      outboxes.push(outbox);
+    }
+
+    let mut result = LiftedBool::Undefined;
+    'turns: while main.resource_limit.read().unwrap().not_canceled() {
+      for worker in workers.iter_mut() {
+        worker.resource_limit.write().unwrap().push(steps_per_turn);
+        let worker_result = worker.check(&LiteralVector::new());
+        worker.resource_limit.write().unwrap().pop();
+
+        // A worker that ran out of memory can't go on learning, so its unknown result is final.
+        // Otherwise an unknown result just means its turn is over.
+        if worker_result != LiftedBool::Undefined || worker.is_memory_exhausted() {
+          main.adopt_result(worker, worker_result);
+          result = worker_result;
+          break 'turns;
+        }
+      }
+
+      // Exchange clauses in worker order. The glue of a lemma isn't known here, so only the size bound
+      // of `enable_add` applies.
+      for i in 0..num_workers {
+        let lemmas = std::mem::take(&mut *outboxes[i].borrow_mut());
+        let max_size = main.get_config().share_max_size as usize;
+        for lemma in lemmas.iter().filter(|lemma| lemma.len() <= max_size) {
+          for (j, worker) in workers.iter_mut().enumerate() {
+            if j != i {
+              worker.import_lemma(lemma);
+            }
+          }
+        }
+      }
+    }
+
+    for _ in 0..num_workers {
+      main.resource_limit.write().unwrap().pop_child();
+    }
+    self.solvers = workers.into_iter().map(Rc::new).collect();
+
+    result
+  }
+
   pub fn push_child(&mut self, rl: ArcRwResourceLimit){ self.resource_limit.push_child(rl); }
 
   pub fn reserve(&mut self, num_owners: usize) {
@@ -164,6 +244,23 @@ impl<'a, 'b> Parallel<'a, 'b> {
 
   pub fn get_solver(&self, i: usize) -> Rc<Solver> { return self.solvers[i].clone(); }
 
+  /// Sums the counters of every worker and of `main`, the solver the workers were copied from,
+  /// into a single report for the whole portfolio. Rates and other floating-point statistics do not
+  /// add up across solvers, so they are left out.
+  pub fn aggregate_statistics(&self, main: &Solver) -> Statistics {
+    let mut statistics = Statistics::new();
+    for solver in std::iter::once(main).chain(self.solvers.iter().map(|worker| worker.as_ref())) {
+      let mut own = Statistics::new();
+      solver.collect_statistics(&mut own);
+      for (&key, &value) in own.iter() {
+        if let Statistic::Integer(_) = value {
+          statistics.update(key, value);
+        }
+      }
+    }
+    statistics
+  }
+
   pub fn cancel_solver(&self, i: usize) { self.limits[i].cancel(); }
 
   /// Exchange unit literals. This is only used in `Solver::pop_reinit()`.
@@ -204,7 +301,7 @@ impl<'a, 'b> Parallel<'a, 'b> {
 
   /// Add the clause to the shared clause pool.
   pub fn share_clause(&mut self, solver: &mut Solver, clause: &Clause){
-    if solver.get_config().num_threads == 1 || !self.enable_add(clause) || solver.parallel_syncing_clauses {
+    if solver.get_config().num_threads == 1 || !Parallel::enable_add(solver.get_config(), clause) || solver.parallel_syncing_clauses {
       return;
     }
 
@@ -213,7 +310,7 @@ impl<'a, 'b> Parallel<'a, 'b> {
 
     let n = clause.size();
     let owner = solver.parallel_id;
-    log_at_level(3, format!("{}: share {}\n", owner, clause).as_str());
+    log_at_level(Verbosity::Debug, format!("{}: share {}\n", owner, clause).as_str());
     let mut pool = self.pool_lock.lock().unwrap();
 
     pool.add_vector(owner, &clause.iter().map(|v| v.index()).collect());
@@ -231,16 +328,13 @@ impl<'a, 'b> Parallel<'a, 'b> {
     solver.parallel_syncing_clauses = true;
 
     log_at_level(
-      3,
+      Verbosity::Debug,
       format!("{}: share {} {}\n", solver.parallel_id, l1, l2).as_str()
     );
 
     {
-      let _lock = self.mux.lock();
-      self.pool.begin_add_vector(solver.parallel_id.into(), 2);
-      self.pool.add_vector_elem(l1.index());
-      self.pool.add_vector_elem(l2.index());
-      self.pool.end_add_vector();
+      let mut pool = self.pool_lock.lock().unwrap();
+      pool.add_vector(solver.parallel_id as VectorIndex, &vec![l1.index(), l2.index()]);
     }
 
     solver.parallel_syncing_clauses = old_par_syncing_clauses;
@@ -282,7 +376,7 @@ impl<'a, 'b> Parallel<'a, 'b> {
           break;
         }
       }
-      log_at_level(3, format!("{}: retrieve {}", solver.parallel_id, self.literals));
+      log_at_level(Verbosity::Debug, format!("{}: retrieve {}", solver.parallel_id, self.literals));
       log_assert!(n >= 2);
       if usable_clause {
         solver.mk_clause_core(&self.literals, Status::redundant());
@@ -318,8 +412,103 @@ impl<'a, 'b> Parallel<'a, 'b> {
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+  use crate::{data_structures::RandomGenerator, Statistics};
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  /// A random 3-SAT instance of 40 variables near the satisfiability threshold.
+  fn random_instance() -> Solver<'static> {
+    let mut main   = Solver::default();
+    let mut random = RandomGenerator::with_seed(7);
+    let variables: Vec<_> = (0..40).map(|_| main.mk_var(true, true)).collect();
+    for _ in 0..170 {
+      let clause: LiteralVector = (0..3).map(
+        |_| Literal::new(variables[random.at_most(40) as usize], random.at_most(2) == 1)
+      ).collect();
+      main.mk_clause_core(&clause, Status::input());
+    }
+    main
+  }
+
+  /// Solves the same random 3-SAT instance with a deterministic portfolio.
+  fn run_portfolio() -> (LiftedBool, Vec<Statistics>) {
+    let mut main     = random_instance();
+    let mut parallel = Parallel::default();
+    let result       = parallel.run_deterministic(&mut main, 3, 100);
+    let statistics   = (0..3).map(|i| {
+                                   let mut statistics = Statistics::new();
+                                   parallel.get_solver(i).collect_statistics(&mut statistics);
+                                   statistics
+                                 })
+                                 .collect();
+    (result, statistics)
+  }
+
+  #[test]
+  fn share_two_literals() {
+    let mut solver = Solver::default();
+    solver.set_num_threads(2);
+    let l1 = Literal::new(solver.mk_var(true, true), false);
+    let l2 = Literal::new(solver.mk_var(true, true), true);
+
+    let mut parallel = Parallel::new(&solver);
+    parallel.share_literals(&mut solver, l1, l2);
+
+    assert!(!solver.parallel_syncing_clauses);
+    let pool = parallel.pool_lock.lock().unwrap();
+    assert_eq!(pool.vectors, vec![vec![l1.index(), l2.index()]]);
+    assert_eq!(pool.owners, vec![solver.parallel_id as VectorIndex]);
+  }
+
+  #[test]
+  fn sharing_respects_configured_thresholds() {
+    let mut config = Config::default();
+    config.share_max_size    = 5;
+    config.share_max_glue    = 6;
+    config.share_always_glue = 2;
+
+    let clause_of_size = |size: usize, glue: u32| {
+      let mut clause = Clause::new(0, (0..size).map(|v| Literal::new(v, false)).collect(), true);
+      clause.set_glue(glue);
+      clause
+    };
+
+    assert!(Parallel::enable_add(&config, &clause_of_size(5, 4)));
+    assert!(!Parallel::enable_add(&config, &clause_of_size(6, 4)));
+    assert!(!Parallel::enable_add(&config, &clause_of_size(5, 7)));
+    // Clauses of very low glue are shared whatever their size.
+    assert!(Parallel::enable_add(&config, &clause_of_size(6, 2)));
+  }
+
+  #[test]
+  fn deterministic_portfolio_is_reproducible() {
+    let (first_result, first_statistics)   = run_portfolio();
+    let (second_result, second_statistics) = run_portfolio();
+
+    assert_ne!(first_result, LiftedBool::Undefined);
+    assert_eq!(first_result, second_result);
+    assert_eq!(first_statistics, second_statistics);
+  }
+
+  #[test]
+  fn aggregate_statistics_sum_over_workers() {
+    let mut main     = random_instance();
+    let mut parallel = Parallel::default();
+    parallel.run_deterministic(&mut main, 2, 100);
+
+    let conflicts = |solver: &Solver| {
+      let mut statistics = Statistics::new();
+      solver.collect_statistics(&mut statistics);
+      statistics["sat conflicts"]
+    };
+    let expected  = conflicts(&main) + conflicts(&parallel.get_solver(0)) + conflicts(&parallel.get_solver(1));
+
+    let aggregate = parallel.aggregate_statistics(&main);
+    assert_eq!(aggregate["sat conflicts"], expected);
+    assert!(aggregate.iter().all(|(_, value)| matches!(value, Statistic::Integer(_))));
+  }
 }
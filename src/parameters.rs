@@ -42,7 +42,7 @@ pub struct Parameter<'s> {
   description: &'static str
 }
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
 pub struct Parameters<'s> {
   module     : &'s str,
   export     : bool,      // todo: Is this relevant? Kept it from z3.
@@ -58,6 +58,14 @@ impl<'s> Parameters<'s>{
         .get(symbol)
         .and_then(| v | Some(v.value))
   }
+
+  /// Sets the value of the parameter `name`, adding it without a description if it is absent.
+  pub fn set_value(&mut self, name: &'static str, value: ParameterValue<'s>) {
+    self.parameters
+        .entry(name)
+        .and_modify(| parameter | parameter.value = value)
+        .or_insert(Parameter { name, value, description: "" });
+  }
 }
 
 impl<'s> Index<&str> for Parameters<'s>{
@@ -8,10 +8,10 @@ use std::cell::RefCell;
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 use std::sync::{RwLock, Arc, RwLockWriteGuard, Mutex};
-use std::sync::atomic::{AtomicBool, AtomicU32};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 static ZSAT_CANCELED_MSG     : &str = "canceled";
-static ZSAT_MAX_MEMORY_MSG   : &str = "max. memory exceeded";
+pub(crate) static ZSAT_MAX_MEMORY_MSG   : &str = "max. memory exceeded";
 static ZSAT_MAX_SCOPES_MSG   : &str = "max. scopes exceeded";
 static ZSAT_MAX_STEPS_MSG    : &str = "max. steps exceeded";
 static ZSAT_MAX_FRAMES_MSG   : &str = "max. frames exceeded";
@@ -23,7 +23,7 @@ static ZSAT_MAX_RESOURCE_MSG : &str = "max. resource limit exceeded";
 
 pub type ArcRwResourceLimit = Arc<RwLock<ResourceLimit>>;
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+#[derive(Debug, Default)]
 pub struct ResourceLimit {
   //friend class scoped_suspend_rlimit;
   cancel : AtomicU32,
@@ -38,6 +38,20 @@ pub struct ResourceLimit {
   children: Vec<ArcRwResourceLimit>, // todo: Is Arc needed here?
 }
 
+// `AtomicU32` is not `Clone`, so neither can `ResourceLimit` derive it.
+impl Clone for ResourceLimit {
+  fn clone(&self) -> Self {
+    ResourceLimit {
+      cancel  : AtomicU32::new(self.cancel.load(Ordering::SeqCst)),
+      suspend : self.suspend,
+      count   : self.count,
+      limit   : self.limit,
+      limits  : self.limits.clone(),
+      children: self.children.clone(),
+    }
+  }
+}
+
 impl ResourceLimit {
 
   /// Sets `self.cancel` without acquiring a lock from the mutex.
@@ -45,10 +59,15 @@ impl ResourceLimit {
   /// We allow this because we want the
   /// parent to be able to set the `cancel` of its children without acquiring a lock for each child.
   /// Only the parent needs to acquire a lock, and only the parent's `cancel` is set externally.
-  fn set_cancel(&mut self, n: u32) {
-    self.cancel = n.into();
-    for child in &mut self.children{
-      child.set_cancel(n +1)
+  /// Since `cancel` is atomic, a shared reference suffices.
+  fn set_cancel(&self, n: u32) {
+    self.cancel.store(n, Ordering::SeqCst);
+    self.set_children_cancel(n);
+  }
+
+  fn set_children_cancel(&self, n: u32) {
+    for child in self.children.iter() {
+      child.read().unwrap().set_cancel(n);
     }
   }
 
@@ -71,7 +90,7 @@ impl ResourceLimit {
     self.limit = u64::min(new_limit, self.limit);
 
     // todo: Why aren't the children also reset? (Could use `reset_cancel()`.
-    self.cancel = 0.into();
+    self.cancel.store(0, Ordering::SeqCst);
   }
 
   pub fn pop(&mut self){
@@ -79,7 +98,7 @@ impl ResourceLimit {
       self.count = self.limit;
     }
     self.limit = self.limits.pop().unwrap();
-    self.cancel = 0.into();
+    self.cancel.store(0, Ordering::SeqCst);
   }
 
   pub fn push_child(&mut self, resource_limit: ArcRwResourceLimit){
@@ -121,7 +140,7 @@ impl ResourceLimit {
   }
 
   pub fn not_canceled(&self) -> bool {
-    (self.cancel == 0 && self.count <= self.limit) || self.suspend
+    (self.cancel.load(Ordering::SeqCst) == 0 && self.count <= self.limit) || self.suspend
   }
 
   pub fn is_canceled(&self) -> bool {
@@ -129,34 +148,42 @@ impl ResourceLimit {
   }
 
   pub fn get_cancel_msg(&self) -> &'static str {
-    return if self.cancel > 0 {
+    return if self.cancel.load(Ordering::SeqCst) > 0 {
       ZSAT_CANCELED_MSG
     } else {
       ZSAT_MAX_RESOURCE_MSG
     }
   }
 
-  pub fn cancel(&mut self) {
+  /// The number of outstanding cancellations.
+  pub fn cancel_count(&self) -> u32 {
+    self.cancel.load(Ordering::SeqCst)
+  }
+
+  pub fn cancel(&self) {
     // #[allow(dead_code)]
     // let lock = GLOBAL_RESOURCE_LIMIT_MUTEX.lock().unwrap();
-    self.set_cancel(*self.cancel + 1)
+    let n = self.cancel.fetch_add(1, Ordering::SeqCst) + 1;
+    self.set_children_cancel(n);
   }
 
-  pub fn reset_cancel(&mut self){
+  pub fn reset_cancel(&self){
     // #[allow(dead_code)]
     // let lock = GLOBAL_RESOURCE_LIMIT_MUTEX.lock().unwrap();
     self.set_cancel(0)
   }
 
-  pub fn inc_cancel(&mut self) {
+  pub fn inc_cancel(&self) {
     self.cancel();
   }
 
-  pub fn dec_cancel(&mut self) {
+  pub fn dec_cancel(&self) {
     // #[allow(dead_code)]
     // let lock = GLOBAL_RESOURCE_LIMIT_MUTEX.lock().unwrap();
-    if self.cancel > 0 {
-      set_cancel(*self.cancel - 1);
+    // Decrement only if positive. Checking first and then decrementing would race.
+    let decremented = self.cancel.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+    if let Ok(previous) = decremented {
+      self.set_children_cancel(previous - 1);
     }
   }
 
@@ -175,7 +202,7 @@ impl ScopedResourceLimit{
   pub fn new(mut resource_limit: ArcRwResourceLimit, limit: u32) -> ScopedResourceLimit {
     { // Write guard scope
       let mut write_guarded_resource_limit = resource_limit.write().unwrap();
-      write_guarded_resource_limit.deref().push(limit);
+      write_guarded_resource_limit.push(limit);
     }
     ScopedResourceLimit{
       resource_limit
@@ -185,7 +212,7 @@ impl ScopedResourceLimit{
 
 impl Drop for ScopedResourceLimit{
   fn drop(&mut self) {
-    self.resource_limit.pop()
+    self.resource_limit.write().unwrap().pop()
   }
 }
 
@@ -279,8 +306,49 @@ impl Drop for ScopedResourceLimits{
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+  use std::thread;
+
   #[test]
   fn it_works() {
     assert_eq!(2 + 2, 4);
   }
+
+  #[test]
+  fn concurrent_cancels_are_counted() {
+    let resource_limit = Arc::new(ResourceLimit::new());
+
+    let cancelers: Vec<_> = (0..8).map(|_| {
+      let resource_limit = resource_limit.clone();
+      thread::spawn(move || for _ in 0..1000 { resource_limit.cancel(); })
+    }).collect();
+    for canceler in cancelers {
+      canceler.join().unwrap();
+    }
+    assert_eq!(resource_limit.cancel_count(), 8000);
+    assert!(resource_limit.is_canceled());
+
+    // One more decrement than there were cancels, which must not wrap around.
+    let uncancelers: Vec<_> = (0..8).map(|i| {
+      let resource_limit = resource_limit.clone();
+      thread::spawn(move || for _ in 0..(1000 + (i == 0) as u32) { resource_limit.dec_cancel(); })
+    }).collect();
+    for uncanceler in uncancelers {
+      uncanceler.join().unwrap();
+    }
+    assert_eq!(resource_limit.cancel_count(), 0);
+    assert!(resource_limit.not_canceled());
+  }
+
+  #[test]
+  fn cancel_reaches_children() {
+    let mut parent = ResourceLimit::new();
+    let child      = Arc::new(RwLock::new(ResourceLimit::new()));
+    parent.push_child(child.clone());
+
+    parent.cancel();
+    assert!(child.read().unwrap().is_canceled());
+    parent.reset_cancel();
+    assert!(child.read().unwrap().not_canceled());
+  }
 }
@@ -3,47 +3,66 @@ Defines the `SolverCore` trait and its canonical implementation `Solver`.
 */
 
 use std::{
+  cell::RefCell,
   collections::{
+    BTreeSet,
     HashSet,
     HashMap,
   },
+  io::{Read, Write},
+  path::Path,
   rc::Rc,
+  sync::{Arc, RwLock},
 };
 
+use itertools::Itertools;
+
 use crate::{
+  BoolVariable,
   BoolVariableVector,
+  branching::{self, BranchingStrategy},
   clause::{
     ClauseWrapperVector,
-    ClauseVector, Clause,
+    Clause,
+    ClauseOffset,
+    gc_ordering,
+    CLAUSE_ACTIVITY_LIMIT,
   },
-  config::Config,
+  clause_allocator::ClauseAllocator,
+  check_satisfiability::CheckResult,
+  config::{Config, PbResolve, RestartStrategy},
+  dimacs::{parse_dimacs_cnf, parse_icnf, parse_wcnf, read_dimacs_file, IcnfCommand},
+  errors::Error,
+  serialize::{read_clause_database, write_clause_database},
   data_structures::{
+    luby,
     ExponentialMovingAverage,
     RandomGenerator,
-    Statistic,
     Statistics,
+    Stopwatch,
+    TimestampedMarks,
   },
   data_structures::{
     ApproximateSet,
     OredIntegerSet,
   },
+  justification::{Justification, Reason},
   lifted_bool::LiftedBoolVector,
   literal::{
+    canonical_clause,
     Literal,
     LiteralSet,
     LiteralVector,
   },
-  local_search::LocalSearchCore,
+  local_search::{Constraint, LocalSearchCore},
   missing_types::{
     AsymmBranch,
     BinarySPR,
-    ClauseAllocator,
     Cleaner,
     Cuber,
     CutSimplifier,
     DRAT,
     Extension,
-    Justification,
     ModelConverter,
     MUS,
     Parallel,
@@ -53,21 +72,28 @@ use crate::{
     ScopedLimitTrail,
     SearchState,
     Simplifier,
-    Stopwatch,
-    VariableQueue,
   },
   model::Model,
-  parameters::ParametersRef,
+  parameters::{Parameters, ParametersRef},
   ResourceLimit,
   status::Status,
-  watched::WatchList, LiftedBool, log::trace,
+  VariableApproximateSet,
+  watched::{WatchList, Watched}, LiftedBool, log::trace,
+  log::{log_at_level, Verbosity},
+  log_assert,
 };
-use crate::missing_types::MinimalUnsatisfiableSet;
-use crate::resource_limit::ArcRwResourceLimit;
+use crate::mus::MinimalUnsatisfiableSet;
+use crate::observer::SolverObserver;
+use crate::resource_limit::{ArcRwResourceLimit, ScopedResourceLimit, ZSAT_MAX_MEMORY_MSG};
 
 const ENABLE_TERNARY: bool = true;
 
 type LevelApproximateSet = OredIntegerSet<u32, u32>;
+
+/// The member of a `LevelApproximateSet` standing for `level`.
+fn level_bit(level: u32) -> u32 {
+  1 << (level & 31)
+}
 type IndexSet = HashSet<u32>;
 
 struct BinaryClause(Literal, Literal);
@@ -118,26 +144,26 @@ impl SolverStatistics {
   }
 
   pub fn collect_statistics(&self, statistics: &mut Statistics) {
-    statistics["sat mk clause 2ary"]          = Statistic::from(self.mk_bin_clause);
-    statistics["sat mk clause 3ary"]          = Statistic::from(self.mk_ter_clause);
-    statistics["sat mk clause nary"]          = Statistic::from(self.mk_clause);
-    statistics["sat mk var"]                  = Statistic::from(self.mk_var);
-    statistics["sat gc clause"]               = Statistic::from(self.gc_clause);
-    statistics["sat del clause"]              = Statistic::from(self.del_clause);
-    statistics["sat conflicts"]               = Statistic::from(self.conflict);
-    statistics["sat decisions"]               = Statistic::from(self.decision);
-    statistics["sat propagations 2ary"]       = Statistic::from(self.bin_propagate);
-    statistics["sat propagations 3ary"]       = Statistic::from(self.ter_propagate);
-    statistics["sat propagations nary"]       = Statistic::from(self.propagate);
-    statistics["sat restarts"]                = Statistic::from(self.restart);
-    statistics["sat minimized lits"]          = Statistic::from(self.minimized_lits);
-    statistics["sat subs resolution dyn"]     = Statistic::from(self.dyn_sub_res);
-    statistics["sat blocked correction sets"] = Statistic::from(self.blocked_corr_sets);
-    statistics["sat units"]                   = Statistic::from(self.units);
-    statistics["sat elim bool vars res"]      = Statistic::from(self.elim_var_res);
-    statistics["sat elim bool vars bdd"]      = Statistic::from(self.elim_var_bdd);
-    statistics["sat backjumps"]               = Statistic::from(self.backjumps);
-    statistics["sat backtracks"]              = Statistic::from(self.backtracks);
+    statistics.update("sat mk clause 2ary",          self.mk_bin_clause);
+    statistics.update("sat mk clause 3ary",          self.mk_ter_clause);
+    statistics.update("sat mk clause nary",          self.mk_clause);
+    statistics.update("sat mk var",                  self.mk_var);
+    statistics.update("sat gc clause",               self.gc_clause);
+    statistics.update("sat del clause",              self.del_clause);
+    statistics.update("sat conflicts",               self.conflict);
+    statistics.update("sat decisions",               self.decision);
+    statistics.update("sat propagations 2ary",       self.bin_propagate);
+    statistics.update("sat propagations 3ary",       self.ter_propagate);
+    statistics.update("sat propagations nary",       self.propagate);
+    statistics.update("sat restarts",                self.restart);
+    statistics.update("sat minimized lits",          self.minimized_lits);
+    statistics.update("sat subs resolution dyn",     self.dyn_sub_res);
+    statistics.update("sat blocked correction sets", self.blocked_corr_sets);
+    statistics.update("sat units",                   self.units);
+    statistics.update("sat elim bool vars res",      self.elim_var_res);
+    statistics.update("sat elim bool vars bdd",      self.elim_var_bdd);
+    statistics.update("sat backjumps",               self.backjumps);
+    statistics.update("sat backtracks",              self.backtracks);
   }
 
 
@@ -150,7 +176,28 @@ struct Scope {
   pub inconsistent         : bool
 }
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+/// The problem held by a `Solver` at some point: its variables as `(external, decision)` pairs, its
+/// input and learned clauses, and its configuration. See `Solver::snapshot` and `Solver::restore`.
+#[derive(Clone, Debug)]
+pub struct SolverSnapshot<'s> {
+  variables: Vec<(bool, bool)>,
+  clauses  : Vec<LiteralVector>,
+  learned  : Vec<LiteralVector>,
+  config   : Config<'s>,
+}
+
+impl<'s> SolverSnapshot<'s> {
+  pub fn number_of_variables(&self) -> usize {
+    self.variables.len()
+  }
+
+  /// The number of input clauses, including units and binary clauses.
+  pub fn number_of_clauses(&self) -> usize {
+    self.clauses.len()
+  }
+}
+
+// `Solver` is not `Clone`: it holds callbacks and a `dyn LocalSearchCore`. Use `Solver::copy` instead.
 pub struct Solver<'s> {
 
   // Data members that should be in SolverCore.
@@ -169,6 +216,8 @@ pub struct Solver<'s> {
   cls_allocator     : ClauseAllocator,
   cls_allocator_idx : bool,
   rand              : RandomGenerator,
+  tiebreak_rand     : RandomGenerator,  // Used only to draw `tiebreak`
+  tiebreak          : Vec<u32>,         // Per variable, breaks ties between equal activities
   cleaner           : Cleaner,
   model             : Model,
   mc                : ModelConverter,
@@ -182,13 +231,14 @@ pub struct Solver<'s> {
   binspr            : BinarySPR,
   inconsistent      : bool,
   searching         : bool,
+  activities_seeded : bool,              // Whether `init_activity_from_occurrences` has run
 
   // A conflict is usually a single justification. That is, a justification for false. If `not_l` is not
   // `Literal::NULL`, then `conflict` is a justification for `l`, and the conflict is union of `no_l` and `conflict`.
   conflict        : Justification,
   not_l           : Literal,
-  pub clauses     : ClauseVector,
-  learned         : ClauseVector,
+  pub clauses     : Vec<ClauseOffset>, // Offsets into `cls_allocator`
+  learned         : Vec<ClauseOffset>,
   num_frozen      : u32,
   active_vars     : Vec<u32>,
   free_vars       : Vec<u32>,
@@ -197,8 +247,8 @@ pub struct Solver<'s> {
   assignment      : LiftedBoolVector,
   justification   : Vec<Justification>,
   decision        : Vec<bool>,
-  mark            : Vec<bool>,
-  lit_mark        : Vec<bool>,
+  mark            : TimestampedMarks, // Indexed by variable
+  lit_mark        : TimestampedMarks, // Indexed by literal
   pub eliminated  : Vec<bool>,
   external        : Vec<bool>,
   var_scope       : Vec<u32>,
@@ -207,15 +257,10 @@ pub struct Solver<'s> {
   replay_assign   : LiteralVector,
 
   // branch variable selection:
-  activity        : Vec<u32>,
-  activity_inc    : u32,
-  last_conflict   : Vec<u64>,
-  last_propagation: Vec<u64>,
-  participated    : Vec<u64>,
-  canceled        : Vec<u64>,
-  reasoned        : Vec<u64>,
-  action          : i32,
-  step_size       : f64,
+  branching: Option<Box<dyn BranchingStrategy>>, // `None` only while the strategy is being called
+
+  // learned clause retention:
+  clause_activity_inc: f64,
 
   // phase
   pub phase             : Vec<bool>,
@@ -223,6 +268,8 @@ pub struct Solver<'s> {
   pub best_phase_size   : u32,
   prev_phase            : Vec<bool>,
   assigned_since_gc     : Vec<char>,
+  last_value            : Vec<LiftedBool>, // The value each variable took when it was last assigned
+  flips                 : Vec<u32>,        // The times each variable was assigned the opposite of `last_value`
   search_state          : SearchState,
   search_unsat_conflicts: u32,
   search_sat_conflicts  : u32,
@@ -232,7 +279,6 @@ pub struct Solver<'s> {
   rephase_inc           : u32,
   reorder_lim           : u32,
   reorder_inc           : u32,
-  case_split_queue      : VariableQueue,
   qhead                 : u32,
   scope_level           : u32,
   search_level          : u32,
@@ -244,16 +290,19 @@ pub struct Solver<'s> {
   pub trail             : LiteralVector,
   clauses_to_reinit     : ClauseWrapperVector,
   reason_unknown        : String,
-  visited               : Vec<u32>,
-  visited_ts            : u32,
+  memory_exhausted      : bool,          // Set when learning stopped because of `config.max_memory`
+  base_conflict         : Option<LiteralVector>, // The units refuting the problem at the base level, once derived
+  last_status           : LiftedBool,    // Result of the most recent `check`, `Undefined` if it was interrupted
+  visited               : TimestampedMarks,
 
   scopes            : Vec<Scope>,
   vars_lim          : ScopedLimitTrail,
   stopwatch         : Stopwatch,
   pub(crate) parameters : ParametersRef<'s>,
-  clone             : Rc<Solver<'s>>,     // for debugging purposes
+  clone             : Option<Box<Solver<'s>>>, // for debugging purposes
   assumptions       : LiteralVector,      // additional assumptions during check
   assumption_set    : LiteralSet,         // set of enabled assumptions
+  assumption_stack  : LiteralVector,      // assumptions pushed by the user, in effect for every check
   ext_assumption_set: LiteralSet,         // set of enabled assumptions
   core              : LiteralVector,      // unsat core
 
@@ -332,210 +381,366 @@ pub struct Solver<'s> {
   // Auxiliary
   m_antecedents         : HashMap<u32, IndexSet>,
   m_todo_antecedents    : LiteralVector,
-  m_binary_clause_graph : Vec<LiteralVector>,
+  m_binary_clause_graph : Vec<LiteralVector>, // The literals implied by each literal through binary clauses
+
+
+  // Occurrence lists, built on demand for simplification
+  occurrences      : Vec<Vec<ClauseOffset>>, // Indexed by literal
+  occurrences_valid: bool,
+
+  // The canonical (sorted) form of every input clause of two or more literals
+  clause_set   : HashSet<LiteralVector>,
+  // The canonical form of every input clause as it was given, before simplification
+  input_clauses: BTreeSet<LiteralVector>,
+  // The literals of the unit clauses given as input, as opposed to those derived at the base level
+  input_units  : HashSet<Literal>,
 
+  // Invoked with every learned clause as soon as it is finalized
+  learned_callback: Option<Box<dyn FnMut(&LiteralVector)>>,
+  // Invoked with the current statistics every `progress_interval` conflicts
+  progress_callback: Option<Box<dyn FnMut(&Statistics)>>,
+  // Told about decisions, conflicts, restarts, and backtracks
+  observer         : Option<Box<dyn SolverObserver>>,
+  progress_interval: u32,
+
+  // MaxSAT
+  soft_clauses: Vec<(BoolVariable, u64)>, // (relaxation variable, weight) of each soft clause
+
+  // Model enumeration
+  blocking_selector: Option<BoolVariable>, // Assumed true to enable the blocking clauses of `enumerate_models`
 
 }
 
-/*
-impl Default<'s> for Solver<'s> {
+impl<'s> Default for Solver<'s> {
   fn default() -> Self {
+    Self::with_config(Config::default())
+  }
+}
+
+impl<'s> Solver<'s> {
+  /// Creates an empty solver configured by `config`, from which the seeds and schedules start.
+  fn with_config(config: Config<'s>) -> Self {
+
     Self{
-      // Data members that should be in SolverCore.
-      pub resource_limit : ResourceLimit::new(),
+      resource_limit: Arc::new(RwLock::new(ResourceLimit::new())),
 
       checkpoint_enabled: false,
-      config            : Config::d>,
-      statistics        : SolverStatistics,
-      pub ext           : Option<Box<Extension>>,
-      cut_simplifier    : Option<Box<CutSimplifier>>,
-      par               : Parallel,
-      pub drat          : DRAT, // DRAT for generating proofs
-      cls_allocator     : ClauseAllocator,
-      cls_allocator_idx : bool,
-      rand              : RandomGenerator,
-      cleaner           : Cleaner,
-      model             : Model,
-      mc                : ModelConverter,
-      model_is_current  : bool,
-      simplifier        : Simplifier,
-      scc               : SCC,
-      asymm_branch      : AsymmBranch,
-      probing           : Probing,
-      is_probing        : bool,              // defaults to false
-      mus               : MinimalUnsatisfiableSet,               // MUS for minimal core extraction
-      binspr            : BinarySPR,
-      inconsistent      : bool,
-      searching         : bool,
-
-      // A conflict is usually a single justification. That is, a justification for false. If `not_l` is not
-      // `Literal::NULL`, then `conflict` is a justification for `l`, and the conflict is union of `no_l` and `conflict`.
-      conflict        : Justification,
-      not_l           : Literal,
-      pub clauses     : ClauseVector,
-      learned         : ClauseVector,
-      num_frozen      : u32,
-      active_vars     : Vec<u32>,
-      free_vars       : Vec<u32>,
-      vars_to_reinit  : Vec<u32>,
-      pub watches     : Vec<WatchList>,
-      assignment      : LiftedBoolVector,
-      justification   : Vec<Justification>,
-      decision        : Vec<bool>,
-      mark            : Vec<bool>,
-      lit_mark        : Vec<bool>,
-      eliminated      : Vec<bool>,
-      external        : Vec<bool>,
-      var_scope       : Vec<u32>,
-      touched         : Vec<u32>,
-      touch_index     : u32,
-      replay_assign   : LiteralVector,
+      statistics        : SolverStatistics::default(),
+      ext               : None,
+      cut_simplifier    : None,
+      parallel          : None,
+      drat              : Default::default(),
+      cls_allocator     : ClauseAllocator::new(),
+      cls_allocator_idx : false,
+      rand              : RandomGenerator::with_seed(config.random_seed),
+      tiebreak_rand     : RandomGenerator::with_seed(config.tiebreak_seed),
+      tiebreak          : Vec::new(),
+      cleaner           : Default::default(),
+      model             : Model::default(),
+      mc                : Default::default(),
+      model_is_current  : false,
+      simplifier        : Default::default(),
+      scc               : Default::default(),
+      asymm_branch      : Default::default(),
+      probing           : Default::default(),
+      is_probing        : false,
+      mus               : Default::default(),
+      binspr            : Default::default(),
+      inconsistent      : false,
+      searching         : false,
+      activities_seeded : false,
+
+      conflict        : Default::default(),
+      not_l           : Literal::NULL,
+      clauses         : Vec::new(),
+      learned         : Vec::new(),
+      num_frozen      : 0,
+      active_vars     : Vec::new(),
+      free_vars       : Vec::new(),
+      vars_to_reinit  : Vec::new(),
+      watches         : Vec::new(),
+      assignment      : LiftedBoolVector::new(),
+      justification   : Vec::new(),
+      decision        : Vec::new(),
+      mark            : TimestampedMarks::new(),
+      lit_mark        : TimestampedMarks::new(),
+      eliminated      : Vec::new(),
+      external        : Vec::new(),
+      var_scope       : Vec::new(),
+      touched         : Vec::new(),
+      touch_index     : 0,
+      replay_assign   : LiteralVector::new(),
 
       // branch variable selection:
-      activity        : Vec<u32>,
-      activity_inc    : u32,
-      last_conflict   : Vec<u64>,
-      last_propagation: Vec<u64>,
-      participated    : Vec<u64>,
-      canceled        : Vec<u64>,
-      reasoned        : Vec<u64>,
-      action          : i32,
-      step_size       : f64,
+      branching: Some(branching::from_config(&config)),
+
+      // learned clause retention:
+      clause_activity_inc: 1.0,
 
       // phase
-      pub phase             : Vec<bool>,
-      pub best_phase        : Vec<bool>,
-      pub best_phase_size   : u32,
-      prev_phase            : Vec<bool>,
-      assigned_since_gc     : Vec<char>,
-      search_state          : SearchState,
-      search_unsat_conflicts: u32,
-      search_sat_conflicts  : u32,
-      search_next_toggle    : u32,
-      phase_counter         : u32,
-      rephase_lim           : u32,
-      rephase_inc           : u32,
-      reorder_lim           : u32,
-      reorder_inc           : u32,
-      case_split_queue      : VariableQueue,
-      qhead                 : u32,
-      scope_lvl             : u32,
-      search_lvl            : u32,
-      fast_glue_avg         : ExponentialMovingAverage,
-      slow_glue_avg         : ExponentialMovingAverage,
-      fast_glue_backup      : ExponentialMovingAverage,
-      slow_glue_backup      : ExponentialMovingAverage,
-      trail_avg             : ExponentialMovingAverage,
-      pub trail             : LiteralVector,
-      clauses_to_reinit     : ClauseWrapperVector,
-      reason_unknown        : String,
-      visited               : Vec<u32>,
-      visited_ts            : u32,
-
-      scopes            : Vec<Scope>,
-      vars_lim          : ScopedLimitTrail,
-      stopwatch         : Stopwatch,
-      pub(crate) params : ParametersRef<'s>,
-      clone             : Rc<Solver<'s>>,     // for debugging purposes
-      assumptions       : LiteralVector,      // additional assumptions during check
-      assumption_set    : LiteralSet,         // set of enabled assumptions
-      ext_assumption_set: LiteralSet,         // set of enabled assumptions
-      core              : LiteralVector,      // unsat core
-
-      par_id             : u32,
-      par_limit_in       : u32,
-      par_limit_out      : u32,
-      par_num_vars       : u32,
-      par_syncing_clauses: bool,
-
-      cuber         : Box<Cuber>,
-      local_search  : Option<Box<dyn LocalSearchCore>>,
-      aux_statistics: Statistics,
-
-      // -----------------------
-      //
-      // Search
-      //
-      // -----------------------
-
-      m_conflicts_since_init    : u32,  // { 0 };
-      m_restarts                : u32,  // { 0 };
-      m_restart_next_out        : u32,  // { 0 };
-      m_conflicts_since_restart : u32,  // { 0 };
-      m_force_conflict_analysis : bool, // { false };
-      m_simplifications         : u32,  // { 0 };
-      m_restart_threshold       : u32,  // { 0 };
-      m_luby_idx                : u32,  // { 0 };
-      m_conflicts_since_gc      : u32,  // { 0 };
-      m_gc_threshold            : u32,  // { 0 };
-      m_defrag_threshold        : u32,  // { 0 };
-      m_num_checkpoints         : u32,  // { 0 };
-      m_min_d_tk                : f64,  // { 0 } ;
-      m_next_simplify           : u32,  // { 0 };
-      m_simplify_enabled        : bool, // { true };
-      m_restart_enabled         : bool, // { true };
-
-      m_min_core          : LiteralVector,
-      m_min_core_valid    : bool,          // { false };
-
-      m_last_positions    : Vec<usize>,
-      m_last_position_log : u32,
-      m_restart_logs      : u32,
-
-
-      // PROTECTED
-      // -----------------------
-      //
-      // Conflict resolution
-      //
-      // -----------------------
-      m_conflict_lvl    : u32,
-      m_lemma           : LiteralVector,
-      m_ext_antecedents : LiteralVector,
-
-
-      m_diff_levels     : Vec<char>,
-
-      // lemma minimization
-      m_unmark          : BoolVariableVector,
-      m_lvl_set         : LevelApproximateSet,
-      m_lemma_min_stack : LiteralVector,
-
-
-      // -----------------------
-      //
-      // Backtracking
-      //
-      // -----------------------
-
-      m_user_scope_literals : LiteralVector,
-      m_free_var_freeze     : Vec<BoolVariableVector>,
-      m_aux_literals        : LiteralVector,
-      m_user_bin_clauses    : Vec<BinaryClause>,
-
-
-      // Auxiliary
-      m_antecedents         : HashMap<u32, IndexSet>,
-      m_todo_antecedents    : LiteralVector,
-      m_binary_clause_graph : Vec<LiteralVector>,
+      phase                 : Vec::new(),
+      best_phase            : Vec::new(),
+      best_phase_size       : 0,
+      prev_phase            : Vec::new(),
+      assigned_since_gc     : Vec::new(),
+      last_value            : Vec::new(),
+      flips                 : Vec::new(),
+      search_state          : Default::default(),
+      search_unsat_conflicts: 0,
+      search_sat_conflicts  : 0,
+      search_next_toggle    : 0,
+      phase_counter         : 0,
+      rephase_lim           : config.rephase_base,
+      rephase_inc           : 0,
+      reorder_lim           : config.reorder_base,
+      reorder_inc           : 0,
+      qhead                 : 0,
+      scope_level           : 0,
+      search_level          : 0,
+      fast_glue_avg         : ExponentialMovingAverage::new(config.fast_glue_avg),
+      slow_glue_avg         : ExponentialMovingAverage::new(config.slow_glue_avg),
+      fast_glue_backup      : ExponentialMovingAverage::new(config.fast_glue_avg),
+      slow_glue_backup      : ExponentialMovingAverage::new(config.slow_glue_avg),
+      trail_avg             : ExponentialMovingAverage::new(config.slow_glue_avg),
+      trail                 : LiteralVector::new(),
+      clauses_to_reinit     : ClauseWrapperVector::new(),
+      reason_unknown        : String::new(),
+      memory_exhausted      : false,
+      base_conflict         : None,
+      last_status           : LiftedBool::Undefined,
+      visited               : TimestampedMarks::new(),
+
+      scopes            : Vec::new(),
+      vars_lim          : Default::default(),
+      stopwatch         : Default::default(),
+      parameters        : Rc::new(RefCell::new(Parameters::default())),
+      clone             : None,
+      assumptions       : LiteralVector::new(),
+      assumption_set    : LiteralSet::default(),
+      assumption_stack  : LiteralVector::new(),
+      ext_assumption_set: LiteralSet::default(),
+      core              : LiteralVector::new(),
+
+      parallel_id             : 0,
+      parallel_limit_in       : 0,
+      parallel_limit_out      : 0,
+      parallel_variable_count : 0,
+      parallel_syncing_clauses: false,
+
+      cuber         : Box::new(Default::default()),
+      local_search  : None,
+      aux_statistics: Statistics::default(),
+
+      m_conflicts_since_init    : 0,
+      m_restarts                : 0,
+      m_restart_next_out        : 0,
+      m_conflicts_since_restart : 0,
+      m_force_conflict_analysis : false,
+      m_simplifications         : 0,
+      m_restart_threshold       : config.restart_initial,
+      m_luby_idx                : 0,
+      m_conflicts_since_gc      : 0,
+      m_gc_threshold            : config.gc_initial,
+      m_defrag_threshold        : 0,
+      m_num_checkpoints         : 0,
+      m_min_d_tk                : 0.0,
+      m_next_simplify           : 0,
+      m_simplify_enabled        : true,
+      m_restart_enabled         : true,
+
+      m_min_core          : LiteralVector::new(),
+      m_min_core_valid    : false,
+
+      m_last_positions    : Vec::new(),
+      m_last_position_log : 0,
+      m_restart_logs      : 0,
+
+      m_conflict_lvl    : 0,
+      m_lemma           : LiteralVector::new(),
+      m_ext_antecedents : LiteralVector::new(),
 
+      m_diff_levels     : Vec::new(),
+
+      m_unmark          : BoolVariableVector::new(),
+      m_lvl_set         : LevelApproximateSet::new(),
+      m_lemma_min_stack : LiteralVector::new(),
+
+      m_user_scope_literals : LiteralVector::new(),
+      m_free_var_freeze     : Vec::new(),
+      m_aux_literals        : LiteralVector::new(),
+      m_user_bin_clauses    : Vec::new(),
+
+      m_antecedents         : HashMap::new(),
+      m_todo_antecedents    : LiteralVector::new(),
+      m_binary_clause_graph : Vec::new(),
+
+      occurrences      : Vec::new(),
+      occurrences_valid: false,
+
+      clause_set   : HashSet::new(),
+      input_clauses: BTreeSet::new(),
+      input_units  : HashSet::new(),
+
+      learned_callback : None,
+      progress_callback: None,
+      observer         : None,
+      progress_interval: 1,
+
+      soft_clauses: Vec::new(),
+
+      blocking_selector: None,
+
+      config,
     }
   }
 }
-*/
 
 impl<'s> Solver<'s> {
 
+  /// Creates an empty solver with the default configuration that shares `resource_limit` with its
+  /// creator, so that cancelling the limit interrupts the solver.
+  pub fn new(resource_limit: ArcRwResourceLimit) -> Self {
+    Self {
+      resource_limit,
+      ..Self::default()
+    }
+  }
 
-
-  pub fn from_params_limit(params: ParametersRef, resource_limit: ArcRwResourceLimit) -> Self{
+  /// Creates a solver configured by the `sat` module parameters `params`. See `Config::new`.
+  pub fn from_params_limit(params: ParametersRef<'s>, resource_limit: ArcRwResourceLimit) -> Self{
+    let config = Config::new(&params);
     Self{
       parameters: params,
-      resource_limit: resource_limit.clone(),
-      ..Self::default
+      resource_limit,
+      ..Self::with_config(config)
+    }
+  }
+
+
+  /// Creates a solver for the DIMACS CNF problem in the file at `path`. Files ending in `.gz` are
+  /// decompressed first.
+  pub fn from_dimacs_path(path: &Path, resource_limit: ArcRwResourceLimit) -> Result<Self, Error> {
+    let input                     = read_dimacs_file(path)?;
+    let (variable_count, clauses) = parse_dimacs_cnf(&input)?;
+
+    let mut solver = Solver::new(resource_limit);
+    for _ in 0..variable_count {
+      solver.mk_var(true, true);
+    }
+    for clause in clauses {
+      solver.mk_clause_core(clause.literals(), Status::input());
+    }
+
+    Ok(solver)
+  }
+
+  /// Creates a solver for the hard clauses of the WCNF problem read from `reader` and returns it
+  /// with the soft clauses and their weights, for instance for `add_soft_clause`.
+  pub fn from_wcnf_reader<R: Read>(
+    mut reader    : R,
+    resource_limit: ArcRwResourceLimit
+  ) -> Result<(Self, Vec<(LiteralVector, u64)>), Error>
+  {
+    let mut input = String::new();
+    reader.read_to_string(&mut input).map_err(|error| Error::DimacsIo(error.to_string()))?;
+    let problem = parse_wcnf(&input)?;
+
+    let mut solver = Solver::new(resource_limit);
+    for _ in 0..problem.variable_count {
+      solver.mk_var(true, true);
+    }
+    for clause in problem.hard.iter() {
+      solver.mk_clause_core(clause, Status::input());
+    }
+
+    Ok((solver, problem.soft))
+  }
+
+  /// Runs the incremental iCNF problem read from `reader`, adding its clauses and answering its
+  /// queries in order. Variables are created as the literals mention them. Returns the result of
+  /// each query.
+  pub fn run_icnf<R: Read>(&mut self, mut reader: R) -> Result<Vec<LiftedBool>, Error> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input).map_err(|error| Error::DimacsIo(error.to_string()))?;
+
+    let mut results = Vec::new();
+    for command in parse_icnf(&input)? {
+      let literals = match &command {
+        IcnfCommand::AddClause(literals) | IcnfCommand::Assume(literals) => literals,
+      };
+      for literal in literals.iter() {
+        while self.number_of_variables() as BoolVariable <= literal.var() {
+          self.mk_var(true, true);
+        }
+      }
+
+      match command {
+        IcnfCommand::AddClause(literals) => {
+          self.mk_clause_core(&literals, Status::input());
+        }
+        IcnfCommand::Assume(assumptions) => results.push(self.check(&assumptions)),
+      }
+    }
+
+    Ok(results)
+  }
+
+  /// Writes the variables and the input clauses, including units, in the compact binary format of
+  /// the `serialize` module. Learned clauses are not written.
+  pub fn serialize<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+    let (clauses, _) = self.clause_database();
+    write_clause_database(out, self.number_of_variables() as usize, &clauses)
+  }
+
+  /// Creates a solver for a clause database written by `serialize`.
+  pub fn deserialize<R: Read>(mut reader: R, resource_limit: ArcRwResourceLimit) -> Result<Self, Error> {
+    let (variable_count, clauses) = read_clause_database(&mut reader)?;
+
+    let mut solver = Solver::new(resource_limit);
+    for _ in 0..variable_count {
+      solver.mk_var(true, true);
     }
+    for clause in clauses.iter() {
+      solver.mk_clause_core(clause, Status::input());
+    }
+
+    Ok(solver)
+  }
+
+  /// Writes the input clauses in DIMACS CNF. Units are written as unit clauses.
+  pub fn write_dimacs<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+    let (clauses, _) = self.clause_database();
+    self.write_dimacs_clauses(out, &clauses, &[])
+  }
+
+  /// Writes the input clauses followed by the learned clauses in DIMACS CNF, each learned clause
+  /// preceded by the comment line `c learned`, so that the derived clauses can be told apart.
+  pub fn write_dimacs_annotated<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+    let (clauses, learned) = self.clause_database();
+    self.write_dimacs_clauses(out, &clauses, &learned)
   }
 
+  fn write_dimacs_clauses<W: Write>(
+    &self,
+    out    : &mut W,
+    clauses: &[LiteralVector],
+    learned: &[LiteralVector]
+  ) -> std::io::Result<()>
+  {
+    let format_clause = |clause: &LiteralVector| {
+      clause.iter().map(|literal| literal.to_dimacs().to_string()).chain(std::iter::once("0".to_string())).join(" ")
+    };
+
+    writeln!(out, "p cnf {} {}", self.number_of_variables(), clauses.len() + learned.len())?;
+    for clause in clauses.iter() {
+      writeln!(out, "{}", format_clause(clause))?;
+    }
+    for clause in learned.iter() {
+      writeln!(out, "c learned")?;
+      writeln!(out, "{}", format_clause(clause))?;
+    }
+    Ok(())
+  }
 
   pub fn get_config(&self) -> &Config {
     &self.config
@@ -545,6 +750,33 @@ impl<'s> Solver<'s> {
     self.resource_limit.clone()
   }
 
+  pub fn number_of_variables(&self) -> u32 {
+    self.justification.len() as u32
+  }
+
+  /// The number of clauses of every kind: the units fixed at the base level, the binary clauses,
+  /// which live only in the watch lists, and the input and learned clauses in the allocator.
+  pub fn number_of_clauses(&self) -> u32 {
+    let mut count = self.init_trail_size();
+    for (index, watch_list) in self.watches.iter().enumerate() {
+      let literal = !Literal(index);
+      for watched in watch_list.list.iter() {
+        if let Watched::Binary { literal: other, .. } = watched {
+          if literal.index() < other.index() {
+            count += 1;
+          }
+        }
+      }
+    }
+    (count + self.clauses.len() + self.learned.len()) as u32
+  }
+
+  /// The solver's own counters as typed fields. `collect_statistics` also includes the statistics of
+  /// the solver's components, keyed by name.
+  pub fn solver_statistics(&self) -> &SolverStatistics {
+    &self.statistics
+  }
+
   pub fn collect_statistics(&self, st: &mut Statistics){
     self.statistics.collect_statistics(st);
     self.cleaner.collect_statistics(st);
@@ -564,6 +796,17 @@ impl<'s> Solver<'s> {
     st.extend(&self.aux_statistics);
   }
 
+  /// Propagations per second of search, over all checks so far. The stopwatch runs only while
+  /// `check` does, so time spent between checks does not count.
+  pub fn throughput(&self) -> f64 {
+    let seconds = self.stopwatch.elapsed_seconds();
+    if seconds <= 0.0 {
+      return 0.0;
+    }
+    let propagations = self.statistics.propagate + self.statistics.bin_propagate + self.statistics.ter_propagate;
+    propagations as f64 / seconds
+  }
+
   fn set_parallel(&mut self, parallel: &Parallel, parallel_id: usize) {
       self.parallel                 = parallel;
       self.parallel_variable_count  = self.number_of_variables();
@@ -573,15 +816,249 @@ impl<'s> Solver<'s> {
       self.parallel_syncing_clauses = false;
   }
 
+  /// Makes `self`, which must be fresh, a copy of the problem in `src`: its variables and its unit,
+  /// binary, and nonbinary clauses. If `copy_learned`, the learned clauses are copied too. The search
+  /// state is not copied.
+  pub fn copy(&mut self, src: &Solver, copy_learned: bool) {
+    log_assert!(self.number_of_variables() == 0);
+    self.add_snapshot(&src.snapshot(), copy_learned);
+  }
+
+  /// Captures the variables, the clauses, and the configuration, so that `restore` can return to
+  /// them later. Units are captured as unit clauses.
+  pub fn snapshot(&self) -> SolverSnapshot<'s> {
+    let (clauses, learned) = self.clause_database();
+
+    SolverSnapshot {
+      variables: (0..self.number_of_variables() as usize).map(|v| (self.external[v], self.decision[v])).collect(),
+      clauses,
+      learned,
+      config   : self.config.clone(),
+    }
+  }
+
+  /// The input clauses, including the binary clauses kept in the watch lists and the units, which
+  /// have no `Clause` objects of their own. Each is returned as a fresh `Clause`.
+  pub fn iter_all_clauses(&self) -> impl Iterator<Item = Clause> {
+    let (clauses, _) = self.clause_database();
+    clauses.into_iter().enumerate().map(|(id, literals)| Clause::new(id as u32, literals, false))
+  }
+
+  /// Checks that `model` satisfies every input clause. Returns the literals of the first clause it
+  /// leaves unsatisfied otherwise. Variables the model leaves undefined satisfy nothing.
+  pub fn verify_model(&self, model: &Model) -> Result<(), LiteralVector> {
+    match self.iter_all_clauses().find(|clause| !clause.satisfied_by(model)) {
+      Some(clause) => Err(clause.literals().clone()),
+      None         => Ok(()),
+    }
+  }
+
+  /// Lists the literals of every clause as `(input, learned)`. Units are listed as input unit
+  /// clauses, whether they were given or derived.
+  fn clause_database(&self) -> (Vec<LiteralVector>, Vec<LiteralVector>) {
+    let mut clauses = Vec::new();
+    let mut learned = Vec::new();
+
+    for &literal in self.trail[..self.init_trail_size()].iter() {
+      clauses.push(vec![literal]);
+    }
+
+    // Each binary clause is in the watch lists of both its literals. Capture it once, even if it
+    // was added more than once, so that copies made for parallel workers don't inherit duplicates.
+    let mut binaries: HashSet<(Literal, Literal, bool)> = HashSet::new();
+    for (index, watch_list) in self.watches.iter().enumerate() {
+      let literal1 = !Literal(index);
+      for watched in watch_list.list.iter() {
+        if let Watched::Binary { literal: literal2, is_learned } = *watched {
+          if literal1.index() < literal2.index() && binaries.insert((literal1, literal2, is_learned)) {
+            if is_learned {
+              learned.push(vec![literal1, literal2]);
+            } else {
+              clauses.push(vec![literal1, literal2]);
+            }
+          }
+        }
+      }
+    }
+
+    clauses.extend(self.clauses.iter().map(|&offset| self.get_clause(offset).literals().clone()));
+    learned.extend(self.learned.iter().map(|&offset| self.get_clause(offset).literals().clone()));
+
+    (clauses, learned)
+  }
+
+  /// Returns `self` to the state captured by `snapshot`, as if a fresh solver had been given the
+  /// captured problem. The callbacks and the branching strategy are kept, but statistics, learned
+  /// clauses acquired since, and all search state are discarded.
+  pub fn restore(&mut self, snapshot: &SolverSnapshot<'s>) {
+    let mut fresh = Solver::from_params_limit(self.parameters.clone(), self.resource_limit.clone());
+    fresh.config            = snapshot.config.clone();
+    fresh.learned_callback  = self.learned_callback.take();
+    fresh.progress_callback = self.progress_callback.take();
+    fresh.progress_interval = self.progress_interval;
+    fresh.observer          = self.observer.take();
+    // `mk_var` announces the variables to the strategy again.
+    fresh.branching         = self.branching.take();
+    fresh.add_snapshot(snapshot, true);
+
+    *self = fresh;
+  }
+
+  fn add_snapshot(&mut self, snapshot: &SolverSnapshot<'s>, add_learned: bool) {
+    for &(external, decision) in snapshot.variables.iter() {
+      self.mk_var(external, decision);
+    }
+    for clause in snapshot.clauses.iter() {
+      self.mk_clause_core(clause, Status::input());
+    }
+    if add_learned {
+      for clause in snapshot.learned.iter() {
+        self.mk_clause_core(clause, Status::redundant());
+      }
+    }
+  }
+
+  /// The length of the part of the trail assigned at base level.
+  pub(crate) fn init_trail_size(&self) -> usize {
+    match self.scopes.first() {
+      Some(scope) => scope.trail_lim as usize,
+      None        => self.trail.len(),
+    }
+  }
+
+  pub(crate) fn set_random_seed(&mut self, seed: u32) {
+    self.config.random_seed = seed;
+    self.rand.set_seed(seed);
+  }
+
+  /// Seeds the tie-breaking between variables of equal activity, separately from `random_seed`, so
+  /// that varying it explores other search trees while all other randomness stays the same. Only
+  /// variables created afterward are affected.
+  pub fn set_tiebreak_seed(&mut self, seed: u32) {
+    self.config.tiebreak_seed = seed;
+    self.tiebreak_rand.set_seed(seed);
+  }
+
+  /// Draws the tie-breaking key of a new variable. Called by `mk_var` before the branching strategy
+  /// is told about the variable. With the default seed 0, every key is 0 and ties go to the lower
+  /// variable index.
+  fn push_tiebreak(&mut self) {
+    let key = match self.config.tiebreak_seed {
+      0 => 0,
+      _ => self.tiebreak_rand.at_most(branching::TIEBREAK_RANGE),
+    };
+    self.tiebreak.push(key);
+  }
+
+  /// The tie-breaking key of `v`. Higher keys are decided first among equally active variables.
+  pub(crate) fn tiebreak(&self, v: BoolVariable) -> u32 {
+    self.tiebreak.get(v).copied().unwrap_or(0)
+  }
+
+  /// Sets `phase_sticky` in the solver's configuration and in that of its local search. For the
+  /// solver it means saving phases aggressively; for local search, initializing each solution from
+  /// the variables' biases, which are taken from the saved phases, rather than at random.
+  pub fn set_phase_sticky(&mut self, phase_sticky: bool) {
+    self.config.phase_sticky = phase_sticky;
+    if let Some(local_search) = &mut self.local_search {
+      local_search.set_phase_sticky(phase_sticky);
+    }
+  }
+
+  pub(crate) fn set_num_threads(&mut self, num_threads: u32) {
+    self.config.num_threads = num_threads;
+  }
+
+  /// Sets the number of conflicts before the next restart. The restart strategy computes the
+  /// threshold after that restart as usual, so this overrides the schedule only until then.
+  pub fn set_restart_threshold(&mut self, n: u32) {
+    self.m_restart_threshold = n;
+  }
+
+  /// Sets the number of conflicts before the next garbage collection of learned clauses. As with
+  /// `set_restart_threshold`, the schedule resumes after that collection.
+  pub fn set_gc_threshold(&mut self, n: u32) {
+    self.m_gc_threshold = n;
+  }
+
+  /// Takes over the outcome of a `check` done by `worker`, a copy of `self` run by a portfolio.
+  pub(crate) fn adopt_result(&mut self, worker: &Solver, result: LiftedBool) {
+    self.last_status = result;
+    match result {
+      LiftedBool::True      => {
+        self.model            = worker.model.clone();
+        self.model_is_current = true;
+      }
+      LiftedBool::False     => self.core           = worker.core.clone(),
+      LiftedBool::Undefined => self.reason_unknown = worker.reason_unknown.clone(),
+    }
+  }
+
+  /// Learns `lemma`, a lemma shared by another solver. It was derived under that solver's base-level
+  /// assignment, not ours, so unlike our own lemmas it is simplified at the base level first, like
+  /// an input clause.
+  pub(crate) fn import_lemma(&mut self, lemma: &LiteralVector) {
+    self.pop_to_base_level();
+    let mut literals = lemma.clone();
+    if self.simplify_clause(&mut literals) {
+      self.mk_clause_core(&literals, Status::redundant());
+    }
+  }
+
+  /// Creates a fresh variable, growing every per-variable (and per-literal) vector in step. An
+  /// `external` variable is visible to the user and kept in models; only `decision` variables are
+  /// ever branched on.
+  pub fn mk_var(&mut self, external: bool, decision: bool) -> BoolVariable {
+    self.statistics.mk_var += 1;
+    self.model_is_current = false;
+    self.invalidate_occurrence_lists();
+
+    let v = self.justification.len() as BoolVariable;
+    self.watches.push(WatchList::default());
+    self.watches.push(WatchList::default());
+    self.assignment.push(LiftedBool::Undefined);
+    self.assignment.push(LiftedBool::Undefined);
+    self.justification.push(Justification::default());
+    self.decision.push(decision);
+    self.eliminated.push(false);
+    self.external.push(external);
+    self.var_scope.push(self.scope_level);
+    self.touched.push(0);
+    self.phase.push(false);
+    self.best_phase.push(false);
+    self.prev_phase.push(false);
+    self.assigned_since_gc.push('\0');
+    self.last_value.push(LiftedBool::Undefined);
+    self.flips.push(0);
+    self.active_vars.push(v as u32);
+    self.mark.reserve(v + 1);
+    self.lit_mark.reserve(2 * v + 2);
+    self.push_tiebreak();
+
+    self.with_branching(|branching, solver| branching.on_new_variable(solver, v));
+    v
+  }
+
   pub fn mk_clause_core(&mut self, literals: &LiteralVector, status: Status) -> Option<Box<Clause>> {
-    let redundant = status.is_redundant();
+    let original      = literals;
+    let mut literals  = literals.clone();
+    let redundant     = status.is_redundant();
     let literal_count = literals.len();
+    if !self.searching && !self.at_base_level() {
+      // A satisfiable `check` leaves its assignment in place.
+      self.pop_to_base_level();
+    }
+    self.invalidate_occurrence_lists();
+    self.model_is_current = false;
+    if !redundant {
+      self.input_clauses.insert(canonical_clause(original));
+    }
 
     trace!(
       "sat",
       format!(
         "mk_clause: {} {}\n",
-        display_literal_vector(literals),
+        display_literal_vector(&literals),
         if redundant {
           "learned"
         } else {
@@ -590,16 +1067,25 @@ impl<'s> Solver<'s> {
       )
     );
 
+    if redundant && literal_count > 1 && self.memory_exceeded() {
+      // Try to make room before giving up on learning.
+      self.gc();
+      if self.memory_in_use() > self.max_memory_bytes() {
+        self.memory_exhausted = true;
+        self.reason_unknown   = ZSAT_MAX_MEMORY_MSG.to_string();
+        return None;
+      }
+    }
+
     if !redundant || !status.is_satisfied() {
-      let old_sz        = literals.len();
-      let keep          = self.simplify_clause(literals);
+      let keep = self.simplify_clause(&mut literals);
 
       trace!(
         "sat_mk_clause",
         format!(
           "mk_clause (after simp), keep: {}\n{}\n",
           keep,
-          display_literal_vector(literals)
+          display_literal_vector(&literals)
         )
       );
 
@@ -608,29 +1094,37 @@ impl<'s> Solver<'s> {
       }
 
       // If an input clause is simplified, then log the simplified version as learned
-      if self.config.drat && old_sz > literal_count {
-        self.drat.add(literals, status);
+      if self.config.drat && literals.len() < literal_count {
+        self.drat.add(&literals, status);
         // drat_log_clause(literals, status);
       }
 
       self.statistics.non_learned_generation += 1;
 
       if !self.searching {
-        self.mc.add_clause(literals);
+        self.mc.add_clause(&literals);
       }
     }
 
-    match literal_count {
+    match literals.len() {
 
       0 => {
-        self.set_conflict();
+        // Every literal of the clause is false at the base level, so each is responsible.
+        let mut units: LiteralVector = original.iter().map(|&literal| !literal).collect();
+        if original.len() == 1 {
+          units.push(original[0]);
+        }
+        self.set_base_conflict(units);
         return None;
       }
 
       1 => {
         if self.config.drat && (!status.is_satisfied() || status.is_input()) {
           // drat_log_clause(literals, status);
-          self.drat.add(literals, status);
+          self.drat.add(&literals, status);
+        }
+        if !redundant && original.len() == 1 {
+          self.input_units.insert(literals[0]);
         }
         self.assign_unit(literals[0]);
 
@@ -638,6 +1132,10 @@ impl<'s> Solver<'s> {
       }
 
       2 => {
+        if !redundant {
+          self.clause_set.insert(canonical_clause(&literals));
+        }
+        self.add_binary_implications(literals[0], literals[1]);
         self.mk_bin_clause(literals[0], literals[1], status);
         if redundant {
           if let Some(parallel) = self.parallel {
@@ -648,19 +1146,92 @@ impl<'s> Solver<'s> {
       }
 
       3 => {
+        if !redundant {
+          self.clause_set.insert(canonical_clause(&literals));
+        }
         if ENABLE_TERNARY {
-          return self.mk_ter_clause(literals, status);
+          return self.mk_ter_clause(&literals, status);
         }
-        return self.mk_nary_clause(literals, status);
+        return self.mk_nary_clause(&literals, status);
       }
 
       _ => {
-        return self.mk_nary_clause(literals, status);
+        if !redundant {
+          self.clause_set.insert(canonical_clause(&literals));
+        }
+        return self.mk_nary_clause(&literals, status);
       }
 
     }
   }
 
+  /// Binary clauses have no clause object; each literal's watch list holds the other literal.
+  fn mk_bin_clause(&mut self, literal1: Literal, literal2: Literal, status: Status) {
+    let is_learned = status.is_redundant();
+    self.statistics.mk_bin_clause += 1;
+    self.watches[(!literal1).index()].list.push(Watched::Binary { literal: literal2, is_learned });
+    self.watches[(!literal2).index()].list.push(Watched::Binary { literal: literal1, is_learned });
+  }
+
+  /// Ternary clauses are allocated like any other clause but watched on all three literals, each
+  /// watch holding the other two.
+  fn mk_ter_clause(&mut self, literals: &LiteralVector, status: Status) -> Option<Box<Clause>> {
+    self.statistics.mk_ter_clause += 1;
+    let offset = self.allocate_clause(literals, status);
+    self.attach_clause(offset);
+    Some(Box::new(self.get_clause(offset).clone()))
+  }
+
+  fn mk_nary_clause(&mut self, literals: &LiteralVector, status: Status) -> Option<Box<Clause>> {
+    self.statistics.mk_clause += 1;
+    let offset = self.allocate_clause(literals, status);
+    self.attach_clause(offset);
+    Some(Box::new(self.get_clause(offset).clone()))
+  }
+
+  fn allocate_clause(&mut self, literals: &LiteralVector, status: Status) -> ClauseOffset {
+    let learned = status.is_redundant();
+    let offset  = self.cls_allocator.allocate(literals.clone(), learned);
+    if learned {
+      self.learned.push(offset);
+    } else {
+      self.clauses.push(offset);
+    }
+    offset
+  }
+
+  /// Adds the watches of a clause. Clauses of more than three literals watch their first two
+  /// literals, so the caller puts the literals to watch first.
+  fn attach_clause(&mut self, offset: ClauseOffset) {
+    let clause = self.cls_allocator.get(offset);
+    if ENABLE_TERNARY && clause.size() == 3 {
+      let (literal0, literal1, literal2) = (clause[0usize], clause[1usize], clause[2usize]);
+      self.watches[(!literal0).index()].list.push(Watched::Ternary(literal1, literal2));
+      self.watches[(!literal1).index()].list.push(Watched::Ternary(literal0, literal2));
+      self.watches[(!literal2).index()].list.push(Watched::Ternary(literal0, literal1));
+    } else {
+      let (literal0, literal1) = (clause[0usize], clause[1usize]);
+      self.watches[(!literal0).index()].list.push(Watched::Clause { blocked_literal: literal1, clause_offset: offset });
+      self.watches[(!literal1).index()].list.push(Watched::Clause { blocked_literal: literal0, clause_offset: offset });
+    }
+  }
+
+  /// Removes the watches added by `attach_clause`.
+  fn detach_clause(&mut self, offset: ClauseOffset) {
+    let clause = self.cls_allocator.get(offset);
+    if ENABLE_TERNARY && clause.size() == 3 {
+      let (literal0, literal1, literal2) = (clause[0usize], clause[1usize], clause[2usize]);
+      self.watches[(!literal0).index()].erase_watch(Watched::Ternary(literal1, literal2));
+      self.watches[(!literal1).index()].erase_watch(Watched::Ternary(literal0, literal2));
+      self.watches[(!literal2).index()].erase_watch(Watched::Ternary(literal0, literal1));
+    } else {
+      let (literal0, literal1) = (clause[0usize], clause[1usize]);
+      let watched              = Watched::Clause { blocked_literal: literal0, clause_offset: offset };
+      self.watches[(!literal0).index()].erase_watch(watched);
+      self.watches[(!literal1).index()].erase_watch(watched);
+    }
+  }
+
   fn assign(&mut self, literal: Literal, justification: Justification) {
 
     trace!("sat_assign", "{} previous value: {} j: {}\n", literal,  self.value(l), justification);
@@ -678,6 +1249,32 @@ impl<'s> Solver<'s> {
     }
   }
 
+  /// Every assignment ends here, so this is where implications are counted, by the kind of clause
+  /// that fired.
+  fn assign_core(&mut self, literal: Literal, justification: Justification) {
+    match justification.reason() {
+      Reason::Binary(_)     => self.statistics.bin_propagate += 1,
+      Reason::Ternary(_, _) => self.statistics.ter_propagate += 1,
+      Reason::Clause(_)     => self.statistics.propagate += 1,
+      _                     => { /* Decisions, units, and extensions are not counted. */ }
+    }
+
+    let v = literal.var();
+    self.assignment[literal.index()]    = LiftedBool::True;
+    self.assignment[(!literal).index()] = LiftedBool::False;
+    self.justification[v]               = justification;
+    self.phase[v]                       = !literal.sign();
+    self.trail.push(literal);
+
+    let value = LiftedBool::from(!literal.sign());
+    if self.last_value[v] == !value {
+      self.flips[v] += 1;
+    }
+    self.last_value[v] = value;
+
+    self.with_branching(|branching, solver| branching.on_assign(solver, literal));
+  }
+
   fn assign_unit(&mut self, literal: Literal) {
     self.assign(literal, Justification::with_level(0))
   }
@@ -696,12 +1293,12 @@ impl<'s> Solver<'s> {
   // The template bool allows for compile-time optimization based on the value of `lvl0`.
   fn simplify_clause_core<const LEVEL_ZERO: bool>(&self, literals: &mut LiteralVector) -> bool {
     literals.sort_unstable();
-    let previous_literal = Literal::NULL;
-    let j = 0u32;
+    let mut previous_literal = Literal::NULL;
+    let mut j                = 0usize;
 
     for i in 0.. literals.len() {
       let current_literal = literals[i];
-      let value: LiftedBool = self.get_literal_value(current_literal);
+      let mut value: LiftedBool = self.get_literal_value(current_literal);
 
       if !LEVEL_ZERO && self.get_literal_level(current_literal) > 0 {
         value = LiftedBool::Undefined;
@@ -718,7 +1315,7 @@ impl<'s> Solver<'s> {
           if current_literal != previous_literal {
               previous_literal = current_literal;
               if i != j {
-                  std::mem::swap(literals[j], literals[i]);
+                  literals.swap(j, i);
                 }
               j += 1;
           }
@@ -730,7 +1327,7 @@ impl<'s> Solver<'s> {
 
       }
     }
-    // num_lits = j;
+    literals.truncate(j);
     true
   }
 
@@ -738,13 +1335,3419 @@ impl<'s> Solver<'s> {
     self.scope_level == 0
   }
 
-  fn simplify_clause(&self, literals: &mut LiteralVector) -> bool {
-        if self.at_base_level(){
-          return self.simplify_clause_core::<true>(literals);
+  /// Records that the clauses are unsatisfiable without any search, for instance because the empty
+  /// clause or two contradictory units were added. No later clause or assumption can undo this.
+  /// `units` are the unit literals responsible, or empty if the empty clause itself was added or the
+  /// conflict was derived by search.
+  fn set_base_conflict(&mut self, units: LiteralVector) {
+    self.inconsistent = true;
+    if self.base_conflict.is_none() {
+      self.base_conflict = Some(units);
+    }
+  }
+
+  /// Called first by `check`. If the clauses were refuted at the base level, sets the core to the
+  /// responsible units and returns `true`, so that `check` can report `LiftedBool::False` at once.
+  fn detect_base_conflict(&mut self) -> bool {
+    match &self.base_conflict {
+      Some(units) => {
+        self.core             = units.clone();
+        self.last_status      = LiftedBool::False;
+        self.model_is_current = false;
+        self.reason_unknown.clear();
+        true
+      }
+      None => false,
+    }
+  }
+
+  // region Backtracking
+
+  /// Opens a new decision level, for a decision or an assumption.
+  fn push_scope(&mut self) {
+    self.scopes.push(Scope {
+      trail_lim            : self.trail.len() as u32,
+      clauses_to_reinit_lim: self.clauses_to_reinit.len() as u32,
+      inconsistent         : self.inconsistent,
+    });
+    self.scope_level += 1;
+  }
+
+  /// Unassigns every literal above decision level `level` and resolves any conflict, which was
+  /// found above it. Propagation resumes from the first literal unassigned.
+  fn pop_to_level(&mut self, level: u32) {
+    if level >= self.scope_level {
+      return;
+    }
+    let trail_lim  = self.scopes[level as usize].trail_lim as usize;
+    let unassigned = self.trail.split_off(trail_lim);
+    for &literal in unassigned.iter().rev() {
+      self.assignment[literal.index()]    = LiftedBool::Undefined;
+      self.assignment[(!literal).index()] = LiftedBool::Undefined;
+      self.with_branching(|branching, solver| branching.on_unassign(solver, literal.var()));
+    }
+
+    self.qhead        = trail_lim as u32;
+    self.scopes.truncate(level as usize);
+    self.scope_level  = level;
+    self.inconsistent = false;
+    self.conflict     = Justification::default();
+    self.not_l        = Literal::NULL;
+    self.notify_backtrack(level);
+  }
+
+  pub fn pop_to_base_level(&mut self) {
+    self.pop_to_level(0);
+  }
+
+  // endregion Backtracking
+
+  // region Propagation
+
+  /// Records a conflict. If `not_l` is `Literal::NULL`, `justification` is a justification for
+  /// false, for instance a clause all of whose literals are false. Otherwise it justifies `!not_l`,
+  /// which is false. Only the first conflict is kept.
+  fn set_conflict(&mut self, justification: Justification, not_l: Literal) {
+    if self.inconsistent {
+      return;
+    }
+    self.inconsistent = true;
+    self.conflict     = justification;
+    self.not_l        = not_l;
+  }
+
+  /// Propagates the literals of the trail from `qhead` on until every clause is either satisfied,
+  /// has two unassigned literals, or is false. Returns the conflict in the last case; the solver is
+  /// then inconsistent until it backtracks. In debug builds, the watch invariants are checked at the
+  /// fixpoint.
+  pub fn propagate(&mut self) -> Option<Justification> {
+    while !self.inconsistent && (self.qhead as usize) < self.trail.len() {
+      let literal = self.trail[self.qhead as usize];
+      self.qhead += 1;
+      self.propagate_literal(literal);
+    }
+
+    if self.inconsistent {
+      return Some(self.conflict);
+    }
+    // The watches are only sure to be consistent at the fixpoint.
+    #[cfg(debug_assertions)]
+    debug_assert_eq!(self.check_watch_invariants(), Ok(()));
+    None
+  }
+
+  /// Visits the clauses containing `!literal`, which just became false. The watch list is taken
+  /// out of `self.watches` while it is walked; watches that move go to the lists of other literals,
+  /// never to this one, as they move to literals that are not false.
+  fn propagate_literal(&mut self, literal: Literal) {
+    let not_l           = !literal;
+    let level           = self.scope_level;
+    let watch_list      = std::mem::take(&mut self.watches[literal.index()].list);
+    let mut kept        = Vec::with_capacity(watch_list.len());
+    let mut watch_list  = watch_list.into_iter();
+
+    for watched in watch_list.by_ref() {
+      match watched {
+
+        Watched::Binary { literal: literal1, .. } => {
+          kept.push(watched);
+          if self.value(literal1) != LiftedBool::True {
+            self.assign(literal1, Justification::binary(level, not_l));
+          }
         }
-        else {
-          return self.simplify_clause_core::<false>(literals);
+
+        Watched::Ternary(literal1, literal2) => {
+          kept.push(watched);
+          match (self.value(literal1), self.value(literal2)) {
+            | (LiftedBool::False, LiftedBool::False)
+            | (LiftedBool::False, LiftedBool::Undefined) => {
+              self.assign(literal2, Justification::ternary(level, literal1, not_l));
+            }
+            (LiftedBool::Undefined, LiftedBool::False) => {
+              self.assign(literal1, Justification::ternary(level, literal2, not_l));
+            }
+            _ => { /* Satisfied, or two literals are unassigned. */ }
+          }
+        }
+
+        Watched::Clause { blocked_literal, clause_offset } => {
+          if self.value(blocked_literal) == LiftedBool::True {
+            kept.push(watched);
+            continue;
+          }
+
+          // Keep the false literal in position 1, so that position 0 holds the other watch.
+          let clause = self.cls_allocator.get_mut(clause_offset);
+          if clause[0usize] == not_l {
+            clause.swap(0, 1);
+          }
+          let first = clause[0usize];
+          let size  = clause.size() as usize;
+
+          if self.value(first) == LiftedBool::True {
+            kept.push(Watched::Clause { blocked_literal: first, clause_offset });
+            continue;
+          }
+
+          let clause      = self.get_clause(clause_offset);
+          let replacement = (2..size).find(|&k| self.value(clause[k]) != LiftedBool::False);
+          match replacement {
+            Some(k) => {
+              let clause = self.cls_allocator.get_mut(clause_offset);
+              clause.swap(1, k);
+              let new_watch = clause[1usize];
+              self.watches[(!new_watch).index()]
+                  .list
+                  .push(Watched::Clause { blocked_literal: first, clause_offset });
+            }
+            None if self.value(first) == LiftedBool::False => {
+              kept.push(watched);
+              self.set_conflict(Justification::clause(level, clause_offset), Literal::NULL);
+            }
+            None => {
+              kept.push(watched);
+              self.assign(first, Justification::clause(level, clause_offset));
+            }
+          }
         }
+
+        // todo: Extensions propagate their own constraints.
+        Watched::ExtensionConstraint(_) => kept.push(watched),
+
+      }
+
+      if self.inconsistent {
+        break;
+      }
     }
 
+    kept.extend(watch_list);
+    self.watches[literal.index()].list = kept;
+  }
+
+  // endregion Propagation
+
+  // region Conflict resolution
+
+  /// The literals of the reason of `justification`, all false. For a clause, these are its literals
+  /// other than `justified`, the literal it justifies, or all of them if it justifies false.
+  fn reason_literals(&self, justification: Justification, justified: Literal) -> LiteralVector {
+    match justification.reason() {
+      Reason::None                        => LiteralVector::new(),
+      Reason::Binary(literal)             => vec![literal],
+      Reason::Ternary(literal1, literal2) => vec![literal1, literal2],
+      Reason::Clause(offset)              => {
+        let clause = self.get_clause(offset);
+        (0..clause.size() as usize).map(|k| clause[k]).filter(|&literal| literal != justified).collect()
+      }
+      // todo: Extensions explain their own propagations.
+      Reason::External(_)                 => LiteralVector::new(),
+    }
+  }
+
+  /// Derives the first-UIP lemma of `conflict`, a conflict returned by `propagate`, and the level to
+  /// backjump to. Starting from the false literals of the conflict, the literals assigned at the
+  /// conflict level are resolved away in reverse trail order until only one is left, the first
+  /// unique implication point. The lemma is its negation, in position 0, followed by the minimized
+  /// literals of lower levels, the one of the highest level in position 1. Its level is the backjump
+  /// level, or 0 if the lemma is a unit. The lemma is also left in `m_lemma`.
+  pub fn analyze_conflict(&mut self, conflict: Justification) -> (LiteralVector, u32) {
+    let justified = match self.not_l {
+      Literal::NULL => Literal::NULL,
+      not_l         => !not_l,
+    };
+    let mut antecedents = self.reason_literals(conflict, justified);
+    if justified != Literal::NULL {
+      antecedents.push(justified);
+    }
+    self.m_conflict_lvl = antecedents.iter()
+                                     .map(|&literal| self.get_literal_level(literal))
+                                     .max()
+                                     .unwrap_or(0);
+    log_assert!(self.m_conflict_lvl > 0);
+
+    self.mark.clear_all();
+    self.m_unmark.clear();
+    self.m_lemma.clear();
+    self.m_lemma.push(Literal::NULL); // Replaced by the negation of the UIP
+    let mut involved      = BoolVariableVector::new();
+    let mut pending       = 0; // Marked variables of the conflict level not yet resolved
+    let mut index         = self.trail.len();
+    let mut justification = conflict;
+
+    loop {
+      if let Reason::Clause(offset) = justification.reason() {
+        if self.get_clause(offset).is_learned() {
+          self.bump_clause_activity(offset);
+        }
+      }
+
+      for literal in antecedents {
+        let v     = literal.var();
+        let level = self.get_literal_level(literal);
+        if self.mark.is_marked(v) || level == 0 {
+          continue;
+        }
+        self.mark.mark(v);
+        involved.push(v);
+        if level == self.m_conflict_lvl {
+          pending += 1;
+        } else {
+          self.m_lemma.push(literal);
+        }
+      }
+
+      // The latest marked literal of the trail. Resolved variables are unmarked, so that only the
+      // variables of the lemma are marked when minimizing it.
+      let literal = loop {
+        index -= 1;
+        if self.mark.is_marked(self.trail[index].var()) {
+          break self.trail[index];
+        }
+      };
+      self.mark.unmark(literal.var());
+      pending -= 1;
+      if pending == 0 {
+        self.m_lemma[0] = !literal;
+        break;
+      }
+      justification = self.justification[literal.var()];
+      antecedents   = self.reason_literals(justification, literal);
+    }
+
+    self.minimize_lemma();
+    self.minimize_lemma_binary();
+
+    let mut backjump_level = 0;
+    if let Some((position, level)) = (1..self.m_lemma.len())
+        .map(|i| (i, self.get_literal_level(self.m_lemma[i])))
+        .max_by_key(|&(_, level)| level)
+    {
+      self.m_lemma.swap(1, position);
+      backjump_level = level;
+    }
+
+    let lemma = self.m_lemma.clone();
+    self.with_branching(|branching, solver| branching.on_conflict(solver, &lemma, &involved));
+    self.decay_clause_activity();
+    self.notify_learned_clause();
+    self.notify_conflict();
+
+    (lemma, backjump_level)
+  }
+
+  /// Removes the literals of the lemma implied by its other literals, found by walking their
+  /// justifications back to literals of the lemma. `m_lvl_set` holds the levels of the lemma, as a
+  /// walk that reaches a level outside it cannot end in the lemma. Does nothing unless
+  /// `minimize_lemmas` is set.
+  fn minimize_lemma(&mut self) {
+    if !self.config.minimize_lemmas {
+      return;
+    }
+
+    self.m_lvl_set.reset();
+    for i in 1..self.m_lemma.len() {
+      let level = self.get_literal_level(self.m_lemma[i]);
+      self.m_lvl_set.insert(&level_bit(level));
+    }
+
+    let size  = self.m_lemma.len();
+    let mut j = 1;
+    for i in 1..size {
+      let literal = self.m_lemma[i];
+      if !self.has_clausal_reason(literal.var()) || !self.is_redundant(literal) {
+        self.m_lemma[j] = literal;
+        j += 1;
+      }
+    }
+    self.m_lemma.truncate(j);
+    self.statistics.minimized_lits += (size - j) as u32;
+  }
+
+  /// Whether `v` was propagated by a clause, as opposed to being decided, assumed, a unit, or
+  /// propagated by an extension.
+  fn has_clausal_reason(&self, v: BoolVariable) -> bool {
+    !matches!(self.justification[v].reason(), Reason::None | Reason::External(_))
+  }
+
+  /// Whether the false literal `literal` of the lemma is implied by the literals marked in `mark`.
+  /// Variables found to be implied are marked as well, and recorded in `m_unmark` so that the marks
+  /// can be undone if the walk fails.
+  fn is_redundant(&mut self, literal: Literal) -> bool {
+    let top = self.m_unmark.len();
+    self.m_lemma_min_stack.clear();
+    self.m_lemma_min_stack.push(literal);
+
+    while let Some(literal) = self.m_lemma_min_stack.pop() {
+      let justification = self.justification[literal.var()];
+      for antecedent in self.reason_literals(justification, !literal) {
+        let v     = antecedent.var();
+        let level = self.get_literal_level(antecedent);
+        if self.mark.is_marked(v) || level == 0 {
+          continue;
+        }
+        if !self.has_clausal_reason(v) || self.m_lvl_set.must_not_contain(&level_bit(level)) {
+          for v in self.m_unmark.drain(top..) {
+            self.mark.unmark(v);
+          }
+          return false;
+        }
+        self.mark.mark(v);
+        self.m_unmark.push(v);
+        self.m_lemma_min_stack.push(antecedent);
+      }
+    }
+
+    true
+  }
+
+  // endregion Conflict resolution
+
+  // region Debugging
+
+  pub(crate) fn get_clause(&self, offset: ClauseOffset) -> &Clause {
+    self.cls_allocator.get(offset)
+  }
+
+  /// Verifies the two-watched-literal invariants of the solver's watch lists. See
+  /// `check_watch_lists`.
+  #[cfg(debug_assertions)]
+  pub fn check_watch_invariants(&self) -> Result<(), String> {
+    check_watch_lists(
+      &self.watches,
+      |literal| self.get_literal_value(literal),
+      |offset| self.get_clause(offset)
+    )
+  }
+
+  /// Checks every clause of the clause arena, input and learned, for corruption: its size must match
+  /// its literals, it must not contain a literal together with its negation, its variables must
+  /// exist and not be eliminated, and its approximate variable set must match its variables.
+  pub fn check_database_integrity(&self) -> Result<(), String> {
+    let variable_count = self.number_of_variables() as BoolVariable;
+
+    for &offset in self.clauses.iter().chain(self.learned.iter()) {
+      let clause = self.get_clause(offset);
+      if clause.size() as usize != clause.literals().len() {
+        return Err(
+          format!("clause {} has size {} but {} literals", clause, clause.size(), clause.literals().len())
+        );
+      }
+
+      let literals = LiteralSet::from_clause(clause.literals());
+      for &literal in clause.literals().iter() {
+        if literal.var() >= variable_count {
+          return Err(format!("clause {} mentions the nonexistent variable {}", clause, literal.var()));
+        }
+        if self.eliminated[literal.var()] {
+          return Err(format!("clause {} mentions the eliminated variable {}", clause, literal.var()));
+        }
+        if literals.contains(!literal) {
+          return Err(format!("clause {} contains both {} and {}", clause, literal, !literal));
+        }
+      }
+
+      let variables: BoolVariableVector = clause.literals().iter().map(|literal| literal.var()).collect();
+      if !clause.approx().equivalent(&VariableApproximateSet::with_values(&variables)) {
+        return Err(format!("clause {} has a stale approximate variable set", clause));
+      }
+    }
+
+    Ok(())
+  }
+
+  // endregion Debugging
+
+  // region Garbage collection
+
+  /// Bumps the activity of the learned clause at `offset`. Called from conflict analysis for every
+  /// learned clause participating in the conflict.
+  fn bump_clause_activity(&mut self, offset: ClauseOffset) {
+    if self.cls_allocator.get_mut(offset).bump_activity(self.clause_activity_inc) {
+      // Rescale to keep activities representable.
+      for &learned_offset in self.learned.iter() {
+        self.cls_allocator.get_mut(learned_offset).scale_activity(1.0 / CLAUSE_ACTIVITY_LIMIT);
+      }
+      self.clause_activity_inc /= CLAUSE_ACTIVITY_LIMIT;
+    }
+  }
+
+  /// Decays all learned clause activities. Instead of touching every clause, we grow the increment.
+  fn decay_clause_activity(&mut self) {
+    self.clause_activity_inc /= self.config.gc_activity_decay;
+  }
+
+  fn can_delete(&self, clause: &Clause) -> bool {
+    !clause.reinit_stack() && !clause.is_frozen()
+  }
+
+  /// Whether the clause at `offset` justifies an assignment on the trail, in which case conflict
+  /// analysis may still need it. A clause only ever justifies its first literal.
+  fn is_reason(&self, offset: ClauseOffset) -> bool {
+    let literal = self.get_clause(offset)[0usize];
+    self.value(literal) == LiftedBool::True
+        && self.justification[literal.var()].reason() == Reason::Clause(offset)
+  }
+
+  /// Moves the learned clause at `offset` to the core tier, which `gc` never deletes from.
+  pub fn promote_clause(&mut self, offset: ClauseOffset) {
+    sassert!(self.get_clause(offset).is_learned());
+    self.cls_allocator.get_mut(offset).set_core(true);
+  }
+
+  /// Promotes the lemma at `offset` if its glue is at most `gc_small_lbd`. Called from conflict
+  /// analysis once the glue of a new lemma is known.
+  fn promote_if_low_glue(&mut self, offset: ClauseOffset) {
+    if self.get_clause(offset).glue() <= self.config.gc_small_lbd {
+      self.promote_clause(offset);
+    }
+  }
+
+  /// Sorts the transient learned clauses according to the configured `GcStrategy` and deletes the
+  /// worse half. The core tier is sorted to the front and kept whole. Does nothing if `no_gc` is set,
+  /// in which case exceeding the memory limit ends the search instead.
+  fn gc(&mut self) {
+    if self.config.no_gc {
+      return;
+    }
+    let ordering  = gc_ordering(self.config.gc_strategy);
+    let allocator = &self.cls_allocator;
+    self.learned.sort_by(|&a, &b| {
+      let (a, b) = (allocator.get(a), allocator.get(b));
+      b.is_core().cmp(&a.is_core()).then_with(|| ordering(a, b))
+    });
+    self.gc_half();
+  }
+
+  /// Deletes the worse half of the transient tier, assuming the core tier is at the front of
+  /// `self.learned` and the transient tier is sorted best first.
+  fn gc_half(&mut self) {
+    let size      = self.learned.len();
+    let core_size = self.learned
+                        .iter()
+                        .take_while(|&&offset| self.get_clause(offset).is_core())
+                        .count();
+    let new_size  = core_size + (size - core_size) / 2;
+    let worse     = self.learned.split_off(new_size);
+    let threshold = self.gc_glue_threshold();
+
+    for offset in worse {
+      let clause = self.cls_allocator.get(offset);
+      if self.can_delete(clause) && !self.is_reason(offset) && clause.glue() > threshold {
+        self.detach_clause(offset);
+        self.cls_allocator.free(offset);
+      } else {
+        self.learned.push(offset);
+      }
+    }
+
+    self.compact_clauses();
+    let deleted = size - self.learned.len();
+    self.statistics.gc_clause += deleted as u32;
+    log_at_level(
+      Verbosity::Debug,
+      format!("(sat-gc :strategy {:?} :deleted {})\n", self.config.gc_strategy, deleted).as_str()
+    );
+  }
+
+  /// Lemmas with glue at most this are spared by `gc_half` even in the worse half. The threshold is
+  /// `gc_glue_factor` times the slow moving average of the glue of recent lemmas, so it rises on
+  /// instances where every lemma has high glue and deletion would otherwise discard useful ones.
+  fn gc_glue_threshold(&self) -> u32 {
+    (self.slow_glue_avg.mean() * self.config.gc_glue_factor) as u32
+  }
+
+  /// Compacts the clause arena and rewrites every stored `ClauseOffset` to match.
+  fn compact_clauses(&mut self) {
+    if self.cls_allocator.free_count() == 0 {
+      return;
+    }
+    let remap = self.cls_allocator.collect_garbage();
+
+    for offset in self.clauses.iter_mut().chain(self.learned.iter_mut()) {
+      *offset = remap[*offset].expect("a freed clause is still in the clause database");
+    }
+    for watch_list in self.watches.iter_mut() {
+      // Watches of freed clauses are removed by `detach_clause`.
+      for watched in watch_list.list.iter_mut() {
+        if let Watched::Clause { clause_offset, .. } = watched {
+          *clause_offset = remap[*clause_offset].expect("a freed clause is still watched");
+        }
+      }
+    }
+    for &literal in self.trail.iter() {
+      // `gc` spares reasons, but simplification deletes satisfied clauses, which include the reasons of
+      // literals fixed at the base level. Those no longer need one.
+      let justification = &mut self.justification[literal.var()];
+      if let Reason::Clause(offset) = justification.reason() {
+        *justification = match remap[offset] {
+          Some(offset) => Justification::clause(justification.level(), offset),
+          None         => Justification::with_level(justification.level()),
+        };
+      }
+    }
+
+    self.invalidate_occurrence_lists();
+  }
+
+  // endregion Garbage collection
+
+  // region Memory
+
+  /// An estimate, in bytes, of the memory used by the clause database and the watch lists.
+  pub fn memory_in_use(&self) -> u64 {
+    let clause_bytes = self.cls_allocator.memory_size();
+    let watch_bytes: usize = self.watches
+                                 .iter()
+                                 .map(|watch_list| watch_list.list.capacity() * std::mem::size_of::<Watched>())
+                                 .sum();
+    (clause_bytes + watch_bytes) as u64
+  }
+
+  fn max_memory_bytes(&self) -> u64 {
+    self.config.max_memory.saturating_mul(1024 * 1024)
+  }
+
+  /// Estimating memory use walks the whole clause database, so, as in z3, we only actually check
+  /// every tenth call.
+  fn memory_exceeded(&mut self) -> bool {
+    self.m_num_checkpoints += 1;
+    if self.m_num_checkpoints < 10 {
+      return false;
+    }
+    self.m_num_checkpoints = 0;
+    self.memory_in_use() > self.max_memory_bytes()
+  }
+
+  /// Whether learning has stopped because the memory limit was reached. The search gives up with
+  /// `LiftedBool::Undefined` when this is set.
+  pub fn is_memory_exhausted(&self) -> bool {
+    self.memory_exhausted
+  }
+
+  pub fn reason_unknown(&self) -> &str {
+    &self.reason_unknown
+  }
+
+  // endregion Memory
+
+  // region Checking
+
+  /// Checks the satisfiability of the clauses under `assumptions` and the assumption stack by
+  /// conflict-driven search. On `LiftedBool::True` the model is current and the assignment is left
+  /// in place until the next clause is added; on `LiftedBool::False` the core holds the assumptions
+  /// responsible, and on `LiftedBool::Undefined` the resource limit or the memory limit stopped the
+  /// search, and `reason_unknown` says which.
+  ///
+  /// If the clauses alone are unsatisfiable, the core does not hold assumptions. When a clause added
+  /// at the base level was already falsified by units, the core holds those units, as recorded by
+  /// `set_base_conflict`, on this and every later call. When the search itself derives a conflict at
+  /// the base level, the responsible units are not tracked, and the core is empty.
+  pub fn check(&mut self, assumptions: &LiteralVector) -> LiftedBool {
+    self.stopwatch.start();
+    self.searching = true;
+    let result     = self.search(assumptions);
+    self.searching = false;
+    self.stopwatch.stop();
+
+    self.last_status = result;
+    if result == LiftedBool::True {
+      self.model            = self.build_model_from_assignment();
+      self.model_is_current = true;
+    }
+    result
+  }
+
+  /// The search loop of `check`: propagate, and on a conflict learn a lemma and backjump, otherwise
+  /// assume the next assumption or decide, until every variable is assigned or a conflict is found
+  /// at the base level or against an assumption.
+  fn search(&mut self, assumptions: &LiteralVector) -> LiftedBool {
+    self.pop_to_base_level();
+    self.model_is_current = false;
+    self.m_min_core_valid = false;
+    self.memory_exhausted = false;
+    self.reason_unknown.clear();
+    self.core.clear();
+    if self.detect_base_conflict() {
+      return LiftedBool::False;
+    }
+
+    if self.config.init_activity_occurrences && !self.activities_seeded {
+      self.init_activity_from_occurrences();
+      self.activities_seeded = true;
+    }
+    self.assumptions               = self.active_assumptions(assumptions);
+    self.m_conflicts_since_restart = 0;
+
+    loop {
+      if let Some(conflict) = self.propagate() {
+        self.statistics.conflict       += 1;
+        self.m_conflicts_since_init    += 1;
+        self.m_conflicts_since_restart += 1;
+        self.m_conflicts_since_gc      += 1;
+
+        if self.at_base_level() {
+          // The clauses alone are unsatisfiable. The conflict stays in place for `final_conflict`.
+          self.m_lemma.clear();
+          self.notify_conflict();
+          self.notify_progress();
+          self.set_base_conflict(LiteralVector::new());
+          return LiftedBool::False;
+        }
+
+        let (lemma, backjump_level) = self.analyze_conflict(conflict);
+        let glue                    = self.lemma_glue(&lemma);
+        self.fast_glue_avg.update(glue as f64);
+        self.slow_glue_avg.update(glue as f64);
+        if backjump_level + 1 < self.scope_level {
+          self.statistics.backjumps += 1;
+        } else {
+          self.statistics.backtracks += 1;
+        }
+        self.pop_to_level(backjump_level);
+        self.learn_lemma(&lemma, glue);
+        self.notify_progress();
+
+        if self.memory_exhausted {
+          return LiftedBool::Undefined;
+        }
+        if !self.resource_limit.write().unwrap().inc() {
+          self.reason_unknown = self.resource_limit.read().unwrap().get_cancel_msg().to_string();
+          return LiftedBool::Undefined;
+        }
+        if self.should_restart() || self.should_random_restart() {
+          self.restart();
+        }
+        continue;
+      }
+
+      // Each assumption gets a decision level of its own, even if it is already true, so that the
+      // assumptions in effect are exactly those below `self.assumptions.len()`.
+      if (self.scope_level as usize) < self.assumptions.len() {
+        let literal = self.assumptions[self.scope_level as usize];
+        match self.value(literal) {
+          LiftedBool::False     => {
+            self.core = self.assumption_core(literal);
+            return LiftedBool::False;
+          }
+          LiftedBool::True      => self.push_scope(),
+          LiftedBool::Undefined => {
+            self.push_scope();
+            self.assign(literal, Justification::with_level(self.scope_level));
+          }
+        }
+        continue;
+      }
+
+      match self.decide_literal() {
+        None          => return LiftedBool::True,
+        Some(literal) => {
+          self.push_scope();
+          self.statistics.decision += 1;
+          self.assign(literal, Justification::with_level(self.scope_level));
+          self.notify_decision(literal);
+        }
+      }
+    }
+  }
+
+  /// The number of distinct decision levels among the literals of `lemma`, its glue. Called before
+  /// backjumping, while every literal is still assigned.
+  fn lemma_glue(&self, lemma: &LiteralVector) -> u32 {
+    let mut levels: Vec<u32> = lemma.iter().map(|&literal| self.get_literal_level(literal)).collect();
+    levels.sort_unstable();
+    levels.dedup();
+    levels.len() as u32
+  }
+
+  /// Adds `lemma`, as returned by `analyze_conflict`, to the learned clauses after backjumping, and
+  /// asserts its first literal, which every other literal of the lemma now forces. A unit lemma is
+  /// asserted at the base level by `mk_clause_core` itself.
+  fn learn_lemma(&mut self, lemma: &LiteralVector, glue: u32) {
+    let level = self.scope_level;
+    self.mk_clause_core(lemma, Status::redundant());
+    if self.memory_exhausted {
+      return;
+    }
+
+    let justification = match lemma.len() {
+      1 => return,
+      2 => Justification::binary(level, lemma[1]),
+      _ => {
+        // `mk_clause_core` may collect garbage first, so the lemma is the last learned clause rather
+        // than at a known position.
+        let offset = *self.learned.last().unwrap();
+        self.cls_allocator.get_mut(offset).set_glue(glue);
+        self.promote_if_low_glue(offset);
+        match lemma.len() == 3 && ENABLE_TERNARY {
+          true  => Justification::ternary(level, lemma[1], lemma[2]),
+          false => Justification::clause(level, offset),
+        }
+      }
+    };
+    self.assign(lemma[0], justification);
+  }
+
+  /// The core when the assumption `failed` is found false: `failed` together with the assumptions
+  /// from which `!failed` was propagated, found by walking the justifications back from `!failed`.
+  /// Only assumptions have been assigned above the base level at this point, so the literals
+  /// without a reason that the walk reaches are assumptions.
+  fn assumption_core(&mut self, failed: Literal) -> LiteralVector {
+    let mut core = vec![failed];
+    if self.get_literal_level(failed) == 0 {
+      return core;
+    }
+
+    self.mark.clear_all();
+    self.mark.mark(failed.var());
+    for i in (self.init_trail_size()..self.trail.len()).rev() {
+      let literal = self.trail[i];
+      if !self.mark.is_marked(literal.var()) {
+        continue;
+      }
+      let justification = self.justification[literal.var()];
+      if justification.is_none() {
+        core.push(literal);
+        continue;
+      }
+      for antecedent in self.reason_literals(justification, literal) {
+        if self.get_literal_level(antecedent) > 0 {
+          self.mark.mark(antecedent.var());
+        }
+      }
+    }
+    core
+  }
+
+  /// Like `check`, but returns the model, the core, or the reason the result is unknown along with
+  /// the status.
+  pub fn check_rich(&mut self, assumptions: &LiteralVector) -> CheckResult {
+    match self.check(assumptions) {
+      LiftedBool::True      => CheckResult::Satisfiable(self.model.clone()),
+      LiftedBool::False     => CheckResult::Unsatisfiable(self.core.clone()),
+      LiftedBool::Undefined => CheckResult::Unknown(self.reason_unknown.clone()),
+    }
+  }
+
+  /// Checks satisfiability under assumptions given as DIMACS integers. `CheckResult::dimacs_core`
+  /// gives the core in the same form.
+  pub fn check_dimacs_assumptions(&mut self, assumptions: &[i32]) -> CheckResult {
+    let assumptions: LiteralVector = assumptions.iter().map(|&value| Literal::from_dimacs(value)).collect();
+    self.check_rich(&assumptions)
+  }
+
+  /// The assumptions responsible for the most recent `check` returning `LiftedBool::False`, or the
+  /// units responsible if the clauses alone are unsatisfiable; see `check`. The core is not
+  /// necessarily minimal; see `minimal_core`.
+  pub fn core(&self) -> &LiteralVector {
+    &self.core
+  }
+
+  /// Pushes `literal` onto the assumption stack. Every `check` assumes the literals on the stack in
+  /// addition to those it is given, until they are popped.
+  pub fn push_assumption(&mut self, literal: Literal) {
+    self.assumption_stack.push(literal);
+    self.assumption_set.insert(literal);
+  }
+
+  /// Pops the most recently pushed assumption, if any.
+  pub fn pop_assumption(&mut self) -> Option<Literal> {
+    let literal = self.assumption_stack.pop()?;
+    if !self.assumption_stack.contains(&literal) {
+      self.assumption_set.remove(literal);
+    }
+    Some(literal)
+  }
+
+  pub fn assumption_stack(&self) -> &LiteralVector {
+    &self.assumption_stack
+  }
+
+  /// The assumptions of a check given `assumptions`: the assumption stack followed by those of
+  /// `assumptions` not already on it.
+  fn active_assumptions(&self, assumptions: &LiteralVector) -> LiteralVector {
+    let mut active = self.assumption_stack.clone();
+    active.extend(assumptions.iter().filter(|literal| !self.assumption_set.contains(**literal)));
+    active
+  }
+
+  /// Fixes `fixed` as units at the base level and checks. Unlike assumptions, the literals stay
+  /// fixed for every later check. If they contradict the clauses, the result is unsatisfiable.
+  pub fn check_with_partial_assignment(&mut self, fixed: &[Literal]) -> CheckResult {
+    for &literal in fixed.iter() {
+      self.mk_clause_core(&vec![literal], Status::input());
+    }
+    self.check_rich(&LiteralVector::new())
+  }
+
+  /// Whether the model belongs to the most recent `check`, which returned `LiftedBool::True`. Adding
+  /// clauses or calling `take_model` invalidates it.
+  pub fn model_is_current(&self) -> bool {
+    self.model_is_current
+  }
+
+  /// Moves the model out of the solver, so that it outlives later changes to the solver. Returns
+  /// `None` if there is no current model, in particular when it has already been taken.
+  pub fn take_model(&mut self) -> Option<Model> {
+    if !self.model_is_current {
+      return None;
+    }
+    self.model_is_current = false;
+    Some(std::mem::take(&mut self.model))
+  }
+
+  /// The model restricted to the external variables. Variables the solver introduced itself, such as
+  /// the relaxation variables of soft clauses, are undefined.
+  pub fn get_external_model(&self) -> Model {
+    self.model.external_only(&self.external)
+  }
+
+  /// Reads the model off the current `assignment`, which holds the value of each variable at the
+  /// index of its positive literal. Eliminated variables are left undefined for the model converter
+  /// to assign.
+  pub fn build_model_from_assignment(&self) -> Model {
+    let mut model = Model::default();
+    for v in 0..self.number_of_variables() as BoolVariable {
+      if self.eliminated[v] {
+        model.push(LiftedBool::Undefined);
+      } else {
+        model.push(self.assignment[Literal::positive(v).index()]);
+      }
+    }
+    model
+  }
+
+  /// Shrinks the core of the most recent `check`, which must have returned `LiftedBool::False`, to a
+  /// minimal unsatisfiable subset of the assumptions, and makes it the core. This takes up to one
+  /// check per literal of the core. The result is kept until the next `check`, so repeated calls
+  /// are free. If a check is interrupted, the core found so far is returned, which is unsatisfiable
+  /// but may not be minimal.
+  pub fn minimal_core(&mut self) -> LiteralVector {
+    if self.m_min_core_valid {
+      return self.m_min_core.clone();
+    }
+    if self.last_status != LiftedBool::False {
+      return LiteralVector::new();
+    }
+
+    let core                  = self.core.clone();
+    let mut mus               = std::mem::take(&mut self.mus);
+    let (minimal, is_minimal) = mus.minimize(self, &core);
+    self.mus                  = mus;
+
+    self.core             = minimal.clone();
+    self.last_status      = LiftedBool::False;
+    self.model_is_current = false;
+    self.m_min_core       = minimal.clone();
+    self.m_min_core_valid = is_minimal;
+    minimal
+  }
+
+  /// Whether the restart schedule calls for a restart after this conflict. Every strategy waits for
+  /// the restart threshold; `RestartStrategy::Ema` additionally waits until the glue of recent
+  /// lemmas exceeds its long-run average by `restart_margin`, a sign that the search is stuck.
+  fn should_restart(&self) -> bool {
+    if !self.m_restart_enabled
+        || self.m_restarts >= self.config.restart_max
+        || self.m_conflicts_since_restart < self.m_restart_threshold
+    {
+      return false;
+    }
+    match self.config.restart {
+      RestartStrategy::Ema => self.fast_glue_avg.mean() >= self.config.restart_margin * self.slow_glue_avg.mean(),
+      _                    => true,
+    }
+  }
+
+  /// Backtracks to the base level, keeping the learned clauses, and schedules the next restart. The
+  /// base level is also where simplification, garbage collection, and reordering the variables are
+  /// safe.
+  fn restart(&mut self) {
+    self.statistics.restart       += 1;
+    self.m_restarts               += 1;
+    self.m_conflicts_since_restart = 0;
+    self.notify_restart();
+    self.pop_to_base_level();
+    self.simplify();
+
+    self.m_restart_threshold = match self.config.restart {
+      RestartStrategy::Geometric => (self.m_restart_threshold as f64 * self.config.restart_factor).ceil() as u32,
+      RestartStrategy::Luby      => {
+        self.m_luby_idx += 1;
+        self.config.restart_initial.saturating_mul(luby(self.m_luby_idx))
+      }
+      RestartStrategy::Ema | RestartStrategy::Static => self.config.restart_initial,
+    };
+
+    if self.m_conflicts_since_gc >= self.m_gc_threshold {
+      self.gc();
+      self.m_conflicts_since_gc = 0;
+      self.m_gc_threshold       = self.m_gc_threshold.saturating_add(self.config.gc_increment);
+    }
+    if self.should_reorder() {
+      self.reorder_variables();
+    }
+  }
+
+  /// Whether to restart after this conflict regardless of the restart schedule. Called after every
+  /// conflict, it restarts with probability `random_restart_freq`.
+  fn should_random_restart(&mut self) -> bool {
+    self.config.random_restart_freq > 0.0 && self.rand.gen_f64() < self.config.random_restart_freq
+  }
+
+  // endregion Checking
+
+  // region Statistics
+
+  /// The result of the most recent `check`. A cancelled or resource-limited check leaves this
+  /// `LiftedBool::Undefined`; see `reason_unknown` for why.
+  pub fn last_status(&self) -> LiftedBool {
+    self.last_status
+  }
+
+  /// Collects the statistics, sorted by name, in the same `(:name value ...)` form z3 prints. The
+  /// counters are updated as the search goes, so after an interrupted `check` they reflect the work
+  /// done before the interruption. The report also gives the `throughput`, which depends on the
+  /// wall clock and so is not among the collected statistics.
+  pub fn statistics_report(&self) -> String {
+    let mut statistics = Statistics::new();
+    self.collect_statistics(&mut statistics);
+    statistics.update("sat props/sec", self.throughput());
+
+    let entries = statistics.iter()
+                            .sorted_by_key(|(name, _)| **name)
+                            .map(|(name, value)| format!(":{} {}", name.replace(' ', "-"), value))
+                            .join("\n ");
+    format!("({})", entries)
+  }
+
+  // endregion Statistics
+
+  // region Callbacks
+
+  /// Registers `callback` to observe every learned clause as it is produced by conflict analysis. This
+  /// can be used for logging or external clause sharing. Replaces any previously registered callback.
+  pub fn set_learned_callback(&mut self, callback: Box<dyn FnMut(&LiteralVector)>) {
+    self.learned_callback = Some(callback);
+  }
+
+  pub fn clear_learned_callback(&mut self) {
+    self.learned_callback = None;
+  }
+
+  /// Passes the finalized lemma to the learned clause callback, if there is one. Called from conflict
+  /// analysis.
+  fn notify_learned_clause(&mut self) {
+    if let Some(callback) = self.learned_callback.as_mut() {
+      callback(&self.m_lemma);
+    }
+  }
+
+  /// Registers `callback` to be called with the current statistics every `every_conflicts` conflicts,
+  /// for reporting the progress of long-running solves. Replaces any previously registered callback.
+  pub fn set_progress_callback(&mut self, every_conflicts: u32, callback: Box<dyn FnMut(&Statistics)>) {
+    log_assert!(every_conflicts > 0);
+    self.progress_interval = every_conflicts;
+    self.progress_callback = Some(callback);
+  }
+
+  pub fn clear_progress_callback(&mut self) {
+    self.progress_callback = None;
+  }
+
+  /// Calls the progress callback if the conflict count has reached a multiple of the interval. Called
+  /// after every conflict.
+  fn notify_progress(&mut self) {
+    if self.progress_callback.is_none() || self.statistics.conflict % self.progress_interval != 0 {
+      return;
+    }
+
+    let mut statistics = Statistics::new();
+    self.collect_statistics(&mut statistics);
+    if let Some(callback) = self.progress_callback.as_mut() {
+      callback(&statistics);
+    }
+  }
+
+  /// Registers `observer` to be told about decisions, conflicts, restarts, and backtracks. Replaces
+  /// any previously registered observer.
+  pub fn set_observer(&mut self, observer: Box<dyn SolverObserver>) {
+    self.observer = Some(observer);
+  }
+
+  pub fn clear_observer(&mut self) {
+    self.observer = None;
+  }
+
+  /// Called after a decision opens a new level.
+  fn notify_decision(&mut self, literal: Literal) {
+    let level = self.scope_level;
+    if let Some(observer) = self.observer.as_mut() {
+      observer.on_decision(literal, level);
+    }
+  }
+
+  /// Called from conflict analysis once the lemma is finalized, and by `check` with an empty lemma
+  /// for the final conflict at the base level.
+  fn notify_conflict(&mut self) {
+    if let Some(observer) = self.observer.as_mut() {
+      observer.on_conflict(&self.m_lemma);
+    }
+  }
+
+  fn notify_restart(&mut self) {
+    if let Some(observer) = self.observer.as_mut() {
+      observer.on_restart();
+    }
+  }
+
+  fn notify_backtrack(&mut self, to_level: u32) {
+    if let Some(observer) = self.observer.as_mut() {
+      observer.on_backtrack(to_level);
+    }
+  }
+
+  // endregion Callbacks
+
+  // region Occurrence lists
+
+  /// Builds, for every literal, the list of clauses in which it occurs. Unlike the watch lists, these
+  /// list every occurrence, which is what variable elimination, subsumption, and pure literal
+  /// detection need. The lists are invalidated whenever the clause database changes and must be
+  /// rebuilt before they are used again.
+  pub fn build_occurrence_lists(&mut self) {
+    for occurrence_list in self.occurrences.iter_mut() {
+      occurrence_list.clear();
+    }
+    // The assignment has one entry per literal.
+    self.occurrences.resize(self.assignment.len(), Vec::new());
+
+    for &offset in self.clauses.iter() {
+      let clause = self.cls_allocator.get(offset);
+      if clause.is_removed() {
+        continue;
+      }
+      for literal in clause.literals() {
+        self.occurrences[literal.index()].push(offset);
+      }
+    }
+
+    self.occurrences_valid = true;
+  }
+
+  pub fn invalidate_occurrence_lists(&mut self) {
+    self.occurrences_valid = false;
+  }
+
+  pub fn occurrence_lists_valid(&self) -> bool {
+    self.occurrences_valid
+  }
+
+  /// The clauses containing `literal`. The occurrence lists must have been built.
+  pub fn occurrences(&self, literal: Literal) -> &[ClauseOffset] {
+    log_assert!(self.occurrences_valid);
+    &self.occurrences[literal.index()]
+  }
+
+  // endregion Occurrence lists
+
+  // region Duplicate detection
+
+  /// Whether an input clause with exactly the literals of `literals`, in any order, has been added.
+  /// Clauses are compared as they were stored, that is, after simplification at the base level.
+  pub fn clause_exists(&self, literals: &LiteralVector) -> bool {
+    self.clause_set.contains(&canonical_clause(literals))
+  }
+
+  /// Adds the clause like `mk_clause_core` unless it is an exact duplicate of an existing input
+  /// clause. Returns whether the clause was added.
+  pub fn add_clause_checked(&mut self, literals: &LiteralVector, status: Status) -> bool {
+    if self.clause_exists(literals) {
+      return false;
+    }
+    self.mk_clause_core(literals, status);
+    true
+  }
+
+  /// A hash of the set of input clauses as they were given, before simplification, that does not
+  /// depend on the order in which the clauses, or the literals within them, were added, nor on
+  /// duplicates. Learned clauses are not included. The hash is 64-bit FNV-1a over the clauses in
+  /// sorted order, each given as the indices of its sorted literals followed by `u64::MAX`, every
+  /// number as eight little-endian bytes. It is stable across runs, platforms, and Rust releases,
+  /// so it can key a persistent cache.
+  pub fn problem_fingerprint(&self) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME       : u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut feed = |value: u64| {
+      for byte in value.to_le_bytes() {
+        hash ^= byte as u64;
+        hash  = hash.wrapping_mul(FNV_PRIME);
+      }
+    };
+    for clause in self.input_clauses.iter() {
+      for literal in clause.iter() {
+        feed(literal.index() as u64);
+      }
+      feed(u64::MAX);
+    }
+    hash
+  }
+
+  // endregion Duplicate detection
+
+  // region Binary implication graph
+
+  /// Records the edges `¬literal1 → literal2` and `¬literal2 → literal1` of the binary clause
+  /// `(literal1 literal2)`.
+  fn add_binary_implications(&mut self, literal1: Literal, literal2: Literal) {
+    let size = usize::max((!literal1).index(), (!literal2).index()) + 1;
+    if self.m_binary_clause_graph.len() < size {
+      self.m_binary_clause_graph.resize(size, LiteralVector::new());
+    }
+    self.m_binary_clause_graph[(!literal1).index()].push(literal2);
+    self.m_binary_clause_graph[(!literal2).index()].push(literal1);
+  }
+
+  /// The literals implied by `literal` through a single binary clause, learned or not.
+  pub fn binary_implications(&self, literal: Literal) -> &LiteralVector {
+    static NO_IMPLICATIONS: LiteralVector = LiteralVector::new();
+    self.m_binary_clause_graph.get(literal.index()).unwrap_or(&NO_IMPLICATIONS)
+  }
+
+  // endregion Binary implication graph
+
+  // region Simplification
+
+  /// Runs the inprocessing passes if simplification is enabled, we are at base level, and enough
+  /// conflicts have happened since the last round. The resource limit is checked between passes.
+  /// Afterward the next round is scheduled `simplify_mult2` times further out, but no more than
+  /// `simplify_max` conflicts away.
+  pub fn simplify(&mut self) {
+    if !self.m_simplify_enabled || !self.at_base_level() || self.m_conflicts_since_init < self.m_next_simplify {
+      return;
+    }
+    log_at_level(Verbosity::Debug, "(sat.simplify)\n");
+
+    self.inprocess();
+
+    self.m_simplifications += 1;
+    if self.m_next_simplify == 0 {
+      self.m_next_simplify = self.config.next_simplify1;
+    } else {
+      self.m_next_simplify = u32::min(
+        (self.m_conflicts_since_init as f64 * self.config.simplify_mult2) as u32,
+        self.m_conflicts_since_init.saturating_add(self.config.simplify_max)
+      );
+    }
+  }
+
+  /// Simplifies the clause database without searching, spending at most `budget` steps of the
+  /// resource limit, so that the simplified instance can be written with `write_dimacs`. If the
+  /// budget runs out, the passes stop early, leaving the instance partially simplified.
+  pub fn preprocess(&mut self, budget: u32) -> Result<(), Error> {
+    if !self.at_base_level() {
+      return Err(Error::NotAtBaseLevel);
+    }
+
+    let _limit = ScopedResourceLimit::new(self.resource_limit.clone(), budget);
+    self.inprocess();
+    Ok(())
+  }
+
+  /// Runs the inprocessing passes. The resource limit is checked between passes.
+  fn inprocess(&mut self) {
+    self.remove_satisfied_clauses();
+    if self.resource_limit.read().unwrap().not_canceled() {
+      self.subsume();
+    }
+    self.compact_clauses();
+  }
+
+  /// Detaches and frees the clause at `offset`. The caller removes it from `self.clauses`.
+  fn delete_clause(&mut self, offset: ClauseOffset) {
+    let literals = canonical_clause(self.cls_allocator.get(offset).literals());
+    self.clause_set.remove(&literals);
+    self.detach_clause(offset);
+    self.cls_allocator.free(offset);
+  }
+
+  /// Deletes the clauses satisfied by the assignment at base level.
+  fn remove_satisfied_clauses(&mut self) {
+    let satisfied: Vec<ClauseOffset> =
+        self.clauses
+            .iter()
+            .copied()
+            .filter(|&offset| {
+              self.cls_allocator
+                  .get(offset)
+                  .literals()
+                  .iter()
+                  .any(|&literal| self.get_literal_value(literal) == LiftedBool::True)
+            })
+            .collect();
+
+    for &offset in satisfied.iter() {
+      self.delete_clause(offset);
+    }
+    let allocator = &self.cls_allocator;
+    self.clauses.retain(|&offset| allocator.is_live(offset));
+  }
+
+  /// Deletes the clauses subsumed by another clause, including by binary clauses, which only exist in
+  /// the watch lists. Of two identical clauses, the later one is deleted. Learned binary clauses are
+  /// not used, since they aren't exported with the input clauses.
+  fn subsume(&mut self) {
+    self.build_occurrence_lists();
+    let mut subsumed: HashSet<ClauseOffset> = HashSet::new();
+
+    for &offset in self.clauses.iter() {
+      // Each clause examined costs a step of the resource limit.
+      if !self.resource_limit.write().unwrap().inc() {
+        break;
+      }
+      let clause = self.cls_allocator.get(offset);
+
+      let subsumed_by_clause = clause.literals().iter().any(|&literal| {
+        self.occurrences[literal.index()].iter().any(|&other_offset| {
+          let other = self.cls_allocator.get(other_offset);
+          other_offset != offset
+            && !subsumed.contains(&other_offset)
+            && (other.size() < clause.size() || (other.size() == clause.size() && other_offset < offset))
+            && other.literals().iter().all(|&other_literal| clause.contains_literal(other_literal))
+        })
+      });
+
+      let subsumed_by_binary = clause.literals().iter().tuple_combinations().any(|(&literal1, &literal2)| {
+        self.watches[(!literal1).index()].list.iter().any(
+          |watched| matches!(*watched, Watched::Binary { literal, is_learned: false } if literal == literal2)
+        )
+      });
+
+      if subsumed_by_clause || subsumed_by_binary {
+        subsumed.insert(offset);
+      }
+    }
+
+    for &offset in subsumed.iter() {
+      self.delete_clause(offset);
+    }
+    self.clauses.retain(|offset| !subsumed.contains(offset));
+    self.invalidate_occurrence_lists();
+  }
+
+  /// Looks for an autarky among the unassigned variables: a set of literals such that every clause
+  /// mentioning one of their variables is satisfied by one of them. Assigning an autarky never makes
+  /// a satisfiable problem unsatisfiable, so its literals can be fixed permanently.
+  ///
+  /// The candidate starts as the saved phase of every unassigned variable. Every clause that the
+  /// candidate touches but does not satisfy has its variables removed from the candidate, until no
+  /// such clause remains. Clauses satisfied at the base level are ignored. Returns `None` if the
+  /// candidate becomes empty or the solver is not at the base level.
+  pub fn find_autarky(&self) -> Option<LiteralVector> {
+    if !self.at_base_level() {
+      return None;
+    }
+    let (clauses, learned) = self.clause_database();
+    let clauses: Vec<&LiteralVector> =
+      clauses.iter()
+             .chain(learned.iter())
+             .filter(|clause| !clause.iter().any(|&literal| self.value(literal) == LiftedBool::True))
+             .collect();
+
+    let mut candidate = LiteralSet::new();
+    for v in 0..self.number_of_variables() as BoolVariable {
+      if !self.eliminated[v] && self.value(Literal::positive(v)) == LiftedBool::Undefined {
+        candidate.insert(Literal::new(v, !self.phase[v]));
+      }
+    }
+
+    let mut changed = true;
+    while changed {
+      changed = false;
+      for clause in clauses.iter() {
+        let touched   = clause.iter().any(|&literal| candidate.contains(!literal));
+        let satisfied = clause.iter().any(|&literal| candidate.contains(literal));
+        if touched && !satisfied {
+          for &literal in clause.iter() {
+            candidate.remove(!literal);
+          }
+          changed = true;
+        }
+      }
+    }
+
+    match candidate.is_empty() {
+      true  => None,
+      false => Some(candidate.to_vector()),
+    }
+  }
+
+  // endregion Simplification
+
+  // region Analysis
+
+  /// Counts the variables as `(active, eliminated, frozen)`. A variable is active unless it was
+  /// eliminated by simplification. Frozen variables, which simplification may not eliminate, are
+  /// among the active ones.
+  pub fn variable_stats(&self) -> (usize, usize, usize) {
+    let eliminated = self.eliminated.iter().filter(|&&eliminated| eliminated).count();
+    let active     = self.number_of_variables() as usize - eliminated;
+    (active, eliminated, self.num_frozen as usize)
+  }
+
+  /// Counts the variables the last model assigns as `(true, false)`.
+  pub fn polarity_stats(&self) -> (u32, u32) {
+    let mut counts = (0, 0);
+    for v in 0..self.number_of_variables() as BoolVariable {
+      match self.model.get(v) {
+        Some(LiftedBool::True)  => counts.0 += 1,
+        Some(LiftedBool::False) => counts.1 += 1,
+        _                       => { /* pass */ }
+      }
+    }
+    counts
+  }
+
+  /// The number of times `v` was assigned the opposite of the value it had when it was last
+  /// assigned, over all searches so far.
+  pub fn flip_count(&self, v: BoolVariable) -> u32 {
+    self.flips[v]
+  }
+
+  /// The input clauses with exactly `n` literals. Binary clauses have no `Clause` object; they live
+  /// only in the watch lists, so `clauses_of_size(2)` is always empty. Use `size_histogram` to count
+  /// them.
+  pub fn clauses_of_size(&self, n: usize) -> impl Iterator<Item = &Clause> {
+    self.clauses
+        .iter()
+        .map(move |&offset| self.cls_allocator.get(offset))
+        .filter(move |clause| !clause.is_removed() && clause.size() as usize == n)
+  }
+
+  /// The number of input clauses of each size, indexed by size. Includes the binary clauses kept in
+  /// the watch lists.
+  pub fn size_histogram(&self) -> Vec<usize> {
+    let mut histogram = vec![0usize; 3];
+
+    // The binary clause (l1 l2) is watched from both literals. Count it from the smaller one.
+    for (index, watch_list) in self.watches.iter().enumerate() {
+      let literal1 = !Literal(index);
+      for watched in watch_list.list.iter() {
+        if let Watched::Binary { literal: literal2, is_learned: false } = *watched {
+          if literal1.index() < literal2.index() {
+            histogram[2] += 1;
+          }
+        }
+      }
+    }
+
+    for &offset in self.clauses.iter() {
+      let clause = self.cls_allocator.get(offset);
+      if clause.is_removed() {
+        continue;
+      }
+      let size = clause.size() as usize;
+      if histogram.len() <= size {
+        histogram.resize(size + 1, 0);
+      }
+      histogram[size] += 1;
+    }
+
+    histogram
+  }
+
+  /// The degree of each variable in the variable incidence graph, that is, the number of input
+  /// clauses it occurs in, indexed by variable. The clauses are those of the occurrence lists plus
+  /// the binary clauses kept in the watch lists.
+  pub fn variable_degrees(&self) -> Vec<u32> {
+    let mut degrees = vec![0u32; self.number_of_variables() as usize];
+
+    for (index, watch_list) in self.watches.iter().enumerate() {
+      let literal1 = !Literal(index);
+      for watched in watch_list.list.iter() {
+        if let Watched::Binary { literal: literal2, is_learned: false } = *watched {
+          if literal1.index() < literal2.index() {
+            degrees[literal1.var()] += 1;
+            degrees[literal2.var()] += 1;
+          }
+        }
+      }
+    }
+
+    for &offset in self.clauses.iter() {
+      let clause = self.cls_allocator.get(offset);
+      if clause.is_removed() {
+        continue;
+      }
+      for literal in clause.literals() {
+        degrees[literal.var()] += 1;
+      }
+    }
+
+    degrees
+  }
+
+  /// The variable occurring in the most input clauses, the smallest one in case of a tie, or `None`
+  /// if no variable occurs in any clause.
+  pub fn most_constrained_variable(&self) -> Option<BoolVariable> {
+    self.variable_degrees()
+        .iter()
+        .enumerate()
+        .filter(|(_, &degree)| degree > 0)
+        .max_by(|(u, d), (v, e)| d.cmp(e).then(v.cmp(u)))
+        .map(|(v, _)| v)
+  }
+
+  /// The antecedents of `literal`, that is, the true literals that forced it: the negations of the
+  /// other literals of its reason clause. Decisions, assumptions, and units have no antecedents.
+  /// Returns `None` if `literal` is not true or was propagated by an extension, whose reasons are
+  /// not recorded here.
+  pub fn explain(&self, literal: Literal) -> Option<Vec<Literal>> {
+    if self.value(literal) != LiftedBool::True {
+      return None;
+    }
+
+    match self.justification[literal.var()].reason() {
+      Reason::None                        => Some(Vec::new()),
+      Reason::Binary(literal2)            => Some(vec![!literal2]),
+      Reason::Ternary(literal2, literal3) => Some(vec![!literal2, !literal3]),
+      Reason::Clause(offset)              => Some(
+        self.get_clause(offset)
+            .literals()
+            .iter()
+            .filter(|&&other| other != literal)
+            .map(|&other| !other)
+            .collect()
+      ),
+      Reason::External(_)                 => None,
+    }
+  }
+
+  /// The clause that became empty when the clauses were refuted at the base level, with every
+  /// literal false under the trail. Binary and ternary clauses are reconstructed from the
+  /// justification. Returns `None` if there is no base-level conflict, if it was found by an
+  /// extension, or if it came from adding the empty clause, which has no literals to report.
+  pub fn final_conflict(&self) -> Option<LiteralVector> {
+    if !self.inconsistent || !self.at_base_level() {
+      return None;
+    }
+
+    // If `not_l` is set, `conflict` justifies `!not_l`, which is false.
+    let mut clause = match self.conflict.reason() {
+      Reason::None                        => LiteralVector::new(),
+      Reason::Binary(literal)             => vec![literal],
+      Reason::Ternary(literal1, literal2) => vec![literal1, literal2],
+      Reason::Clause(offset)              => self.get_clause(offset).literals().clone(),
+      Reason::External(_)                 => return None,
+    };
+    if self.not_l != Literal::NULL && !clause.contains(&!self.not_l) {
+      clause.push(!self.not_l);
+    }
+
+    if clause.is_empty() {
+      None
+    } else {
+      Some(clause)
+    }
+  }
+
+  /// Drops every literal `x` of the lemma for which there is a binary clause `(l0 ¬x)`, where `l0`
+  /// is the asserting literal `m_lemma[0]`. Resolving the lemma with that clause removes `x` and
+  /// keeps the rest. Called from conflict analysis after recursive minimization.
+  fn minimize_lemma_binary(&mut self) {
+    if !self.config.minimize_lemmas_binary || self.m_lemma.len() <= 1 {
+      return;
+    }
+
+    // The binary clause `(l0 ¬x)` is the implication `¬l0 → ¬x`.
+    let asserting = self.m_lemma[0];
+    let removable = LiteralSet::from_clause(self.binary_implications(!asserting));
+    let size      = self.m_lemma.len();
+    self.m_lemma.retain(|&literal| literal == asserting || !removable.contains(!literal));
+    self.statistics.minimized_lits += (size - self.m_lemma.len()) as u32;
+  }
+
+  /// The literals fixed at the base level other than the input units, that is, those derived by
+  /// propagation or simplification, in the order they were assigned.
+  pub fn derived_units(&self) -> LiteralVector {
+    self.trail[..self.init_trail_size()]
+        .iter()
+        .filter(|literal| !self.input_units.contains(literal))
+        .cloned()
+        .collect()
+  }
+
+  // endregion Analysis
+
+  // region Pseudo-boolean resolution
+
+  /// Resolves the at-most-k constraints `c1` and `c2` on `pivot`, which must occur positively in one
+  /// and negatively in the other. Adding the constraints and using `x + !x = 1` gives
+  ///
+  ///   (L1 - x) + (L2 - !x) <= k1 + k2 - 1.
+  ///
+  /// A literal occurring in both gets coefficient 2, which a cardinality constraint cannot express.
+  /// It is kept once, which weakens the resolvent but keeps it sound.
+  ///
+  /// This is cardinality resolution, used when `config.pb_resolve` is `PbResolve::Cardinality`.
+  /// Rounding resolution needs general coefficients, which `Constraint` does not have.
+  pub fn resolve_cardinality(&mut self, c1: &Constraint, c2: &Constraint, pivot: BoolVariable) -> Constraint {
+    sassert!(self.config.pb_resolve == PbResolve::Cardinality);
+    // Both bounds zero means `x <= 0` and `!x <= 0`, a conflict with no at-most-k resolvent.
+    sassert!(c1.k + c2.k > 0);
+
+    let pivot_literal = *c1.iter()
+                           .find(|literal| literal.var() == pivot)
+                           .expect("the pivot does not occur in the first constraint");
+    sassert!(c2.iter().any(|&literal| literal == !pivot_literal));
+
+    // The resolvent is not part of a local search instance, so its id is meaningless.
+    let mut resolvent = Constraint::new(c1.k + c2.k - 1, 0);
+    for &literal in c1.iter().chain(c2.iter()) {
+      if literal.var() != pivot && !resolvent.iter().any(|&existing| existing == literal) {
+        resolvent.push(literal);
+      }
+    }
+
+    resolvent
+  }
+
+  // endregion Pseudo-boolean resolution
+
+  // region Variable ordering
+
+  /// Replaces the branching heuristic chosen by `config.branching_heuristic`. The strategy is told
+  /// about the variables that already exist.
+  pub fn set_branching_strategy(&mut self, strategy: Box<dyn BranchingStrategy>) {
+    self.branching = Some(strategy);
+    for v in 0..self.number_of_variables() as BoolVariable {
+      self.with_branching(|branching, solver| branching.on_new_variable(solver, v));
+    }
+  }
+
+  /// Calls `f` with the branching strategy and a shared borrow of `self`.
+  fn with_branching<R>(&mut self, f: impl FnOnce(&mut dyn BranchingStrategy, &Solver<'s>) -> R) -> R {
+    let mut branching = self.branching.take().expect("branching strategy called reentrantly");
+    let result        = f(branching.as_mut(), self);
+    self.branching    = Some(branching);
+    result
+  }
+
+  /// Asks the branching strategy for the next decision literal. Propagation is complete and
+  /// conflict free at this point, so this is also where the target phase is saved and, on schedule,
+  /// reinstalled.
+  pub(crate) fn decide_literal(&mut self) -> Option<Literal> {
+    self.update_best_phase();
+    if self.should_rephase() {
+      self.do_rephase();
+    }
+    self.with_branching(|branching, solver| branching.pick(solver))
+  }
+
+  /// Saves the current assignment as the target phase if it is the longest conflict-free trail so
+  /// far. Variables off the trail keep their saved phase, so phase hints survive rephasing.
+  fn update_best_phase(&mut self) {
+    if self.trail.len() as u32 <= self.best_phase_size {
+      return;
+    }
+    self.best_phase_size = self.trail.len() as u32;
+    self.best_phase.copy_from_slice(&self.phase);
+    for &literal in self.trail.iter() {
+      self.best_phase[literal.var()] = !literal.sign();
+    }
+  }
+
+  fn should_rephase(&self) -> bool {
+    self.m_conflicts_since_init > self.rephase_lim
+  }
+
+  /// Reinstalls the target phase as the saved phase of every variable and schedules the next
+  /// rephase, `rephase_base` conflicts further out each time.
+  fn do_rephase(&mut self) {
+    self.phase_counter += 1;
+    if self.best_phase_size > 0 {
+      self.phase.copy_from_slice(&self.best_phase);
+    }
+    self.rephase_inc += self.config.rephase_base;
+    self.rephase_lim  = self.m_conflicts_since_init.saturating_add(self.rephase_inc);
+  }
+
+  /// Saves `value` as the phase of `v`, so that under phase caching the first decision on `v` assigns
+  /// it `value`. Search overwrites the saved phase as usual.
+  pub fn set_phase_hint(&mut self, v: BoolVariable, value: bool) {
+    self.phase[v] = value;
+  }
+
+  /// Hints the phase of every variable the model assigns, to warm-start search from a previous
+  /// solution.
+  pub fn set_phase_hints_from_model(&mut self, model: &Model) {
+    for v in 0..self.number_of_variables() as BoolVariable {
+      match model.get(v) {
+        Some(LiftedBool::True)  => self.set_phase_hint(v, true),
+        Some(LiftedBool::False) => self.set_phase_hint(v, false),
+        _                       => { /* pass */ }
+      }
+    }
+  }
+
+  /// The value of `literal` under the current assignment.
+  pub fn value(&self, literal: Literal) -> LiftedBool {
+    self.get_literal_value(literal)
+  }
+
+  /// Whether the variable of `literal` is assigned at the base level, so that its value is forced
+  /// for every later search. This is the case for units, both input and derived.
+  pub fn is_fixed(&self, literal: Literal) -> bool {
+    self.value(literal) != LiftedBool::Undefined && self.get_literal_level(literal) == 0
+  }
+
+  /// The literals assigned true at the base level, in the order they were assigned.
+  pub fn fixed_literals(&self) -> LiteralVector {
+    self.trail[..self.init_trail_size()].to_vec()
+  }
+
+  /// Whether the solver may branch on `v`.
+  pub fn is_decision(&self, v: BoolVariable) -> bool {
+    self.decision[v]
+  }
+
+  /// Seeds the activity of each variable with the number of input clauses it occurs in, scaled by
+  /// `activity_scale`, so that search starts on the most constrained variables. Called once before
+  /// the first search if `init_activity_occurrences` is set.
+  pub fn init_activity_from_occurrences(&mut self) {
+    let activity_scale = self.config.activity_scale;
+    let activities: Vec<u32> = self.variable_degrees()
+                                   .iter()
+                                   .map(|degree| degree.saturating_mul(activity_scale))
+                                   .collect();
+    self.with_branching(|branching, solver| branching.seed_activities(solver, &activities));
+  }
+
+  fn should_reorder(&self) -> bool {
+    self.m_conflicts_since_init > self.reorder_lim
+  }
+
+  /// Has the branching strategy replace the activities of the unassigned variables by their rank,
+  /// scaled by `reorder_activity_scale`. This discards the magnitudes that accumulated since the last
+  /// reordering while keeping the relative order. Then the next reordering is scheduled
+  /// `reorder_inc` conflicts further out than the previous interval.
+  pub fn reorder_variables(&mut self) {
+    let activity_scale = self.config.reorder_activity_scale;
+    self.with_branching(|branching, solver| branching.reorder(solver, activity_scale));
+
+    self.reorder_inc = self.reorder_inc.saturating_add(self.config.reorder_base);
+    self.reorder_lim = self.reorder_lim.saturating_add(self.reorder_inc);
+  }
+
+  // endregion Variable ordering
+
+  // region MaxSAT
+
+  /// Adds a soft clause with the given `weight`. A fresh relaxation variable `r` is introduced and the
+  /// hard clause `literals ∪ {r}` is added in its place. The soft clause is violated exactly when `r`
+  /// must be true. Returns `r`.
+  pub fn add_soft_clause(&mut self, literals: &LiteralVector, weight: u64) -> BoolVariable {
+    let relaxation_variable = self.mk_var(false, true);
+
+    let mut relaxed_clause = literals.clone();
+    relaxed_clause.push(Literal::new(relaxation_variable, false));
+    self.mk_clause_core(&relaxed_clause, Status::input());
+
+    self.soft_clauses.push((relaxation_variable, weight));
+    relaxation_variable
+  }
+
+  /// Finds a model of the hard clauses minimizing the total weight of violated soft clauses. Returns
+  /// the minimum cost and a model attaining it, or `(0, None)` if the hard clauses are unsatisfiable.
+  ///
+  /// This is a basic linear search over the relaxation variables: after each model, the cost of the
+  /// next one is bounded strictly below the cost of this one, until no cheaper model exists. The
+  /// bounds are read off a single totalizer, built by `mk_totalizer` after the first model, whose
+  /// clauses are all guarded by a literal that is assumed during the search. The cost decreases with
+  /// every check, so there are at most as many checks as distinct achievable costs. If a check gives
+  /// up, the best model found so far is returned.
+  ///
+  /// Before returning, the guard is fixed false. This retires the totalizer and the bounds, which
+  /// the next `simplify` removes, so repeated calls don't accumulate clauses.
+  pub fn solve_maxsat(&mut self) -> (u64, Option<Model>) {
+    let mut best   : Option<(u64, Model)> = None;
+    let mut guard  : Option<Literal>      = None;
+    let mut outputs: Vec<(u64, Literal)>  = Vec::new();
+
+    while self.check(&guard.into_iter().collect()) == LiftedBool::True {
+      let cost: u64 = self.soft_clauses
+                          .iter()
+                          .filter(|(r, _)| self.model[*r] == LiftedBool::True)
+                          .map(|(_, weight)| weight)
+                          .sum();
+      best = Some((cost, self.model.clone()));
+
+      if cost == 0 {
+        break;
+      }
+
+      if guard.is_none() {
+        // The guard is only ever assumed, so it is not a decision variable.
+        let literal = Literal::new(self.mk_var(false, false), false);
+        outputs     = self.mk_totalizer(literal, cost);
+        guard       = Some(literal);
+      }
+      // Look for a strictly cheaper model. The outputs are the achievable costs, capped at the cost of
+      // the first model, so the smallest one not below `cost` is reached exactly when `cost` is.
+      match outputs.iter().find(|(sum, _)| *sum >= cost) {
+        Some(&(_, at_least)) => { self.mk_clause_core(&vec![!guard.unwrap(), !at_least], Status::input()); }
+        None                 => break,
+      }
+    }
+
+    if let Some(guard) = guard {
+      self.mk_clause_core(&vec![!guard], Status::input());
+    }
+
+    match best {
+      Some((cost, model)) => (cost, Some(model)),
+      None                => (0, None)
+    }
+  }
+
+  /// Encodes the sum of the weights of the true relaxation variables as a generalized totalizer, in
+  /// clauses that only hold while `guard` is true. Returns the root of the totalizer: a list of
+  /// `(sum, literal)` pairs, ordered by `sum`, where `literal` is forced true once the weights add up
+  /// to at least `sum`. Sums are capped at `overflow`. Leaving the outputs unforced never violates a
+  /// clause, so they are not decision variables.
+  ///
+  /// The encoding has a variable for every partial sum up to `overflow` that a node can reach, so it
+  /// can be exponential in the number of soft clauses when their weights are all distinct.
+  fn mk_totalizer(&mut self, guard: Literal, overflow: u64) -> Vec<(u64, Literal)> {
+    let mut nodes: Vec<Vec<(u64, Literal)>> =
+        self.soft_clauses
+            .iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|&(r, weight)| vec![(weight.min(overflow), Literal::new(r, false))])
+            .collect();
+
+    while nodes.len() > 1 {
+      let mut merged = Vec::with_capacity((nodes.len() + 1) / 2);
+      let mut iter   = nodes.into_iter();
+      while let Some(left) = iter.next() {
+        match iter.next() {
+          Some(right) => merged.push(self.mk_totalizer_node(guard, &left, &right, overflow)),
+          None        => merged.push(left)
+        }
+      }
+      nodes = merged;
+    }
+
+    nodes.pop().unwrap_or_default()
+  }
+
+  /// Merges two nodes of the totalizer built by `mk_totalizer`. Every clause includes `!guard`.
+  fn mk_totalizer_node(
+    &mut self,
+    guard   : Literal,
+    left    : &[(u64, Literal)],
+    right   : &[(u64, Literal)],
+    overflow: u64
+  ) -> Vec<(u64, Literal)> {
+    let mut sums: Vec<u64> = left.iter().chain(right.iter()).map(|&(sum, _)| sum).collect();
+    for &(a, _) in left {
+      sums.extend(right.iter().map(|&(b, _)| a.saturating_add(b).min(overflow)));
+    }
+    sums.sort_unstable();
+    sums.dedup();
+
+    let outputs: Vec<(u64, Literal)> =
+        sums.into_iter()
+            .map(|sum| (sum, Literal::new(self.mk_var(false, false), false)))
+            .collect();
+    let output = |sum: u64| outputs.iter().find(|(s, _)| *s == sum).unwrap().1;
+
+    for &(sum, literal) in left.iter().chain(right.iter()) {
+      self.mk_clause_core(&vec![!guard, !literal, output(sum)], Status::input());
+    }
+    for &(a, left_literal) in left {
+      for &(b, right_literal) in right {
+        let sum = a.saturating_add(b).min(overflow);
+        self.mk_clause_core(&vec![!guard, !left_literal, !right_literal, output(sum)], Status::input());
+      }
+    }
+
+    outputs
+  }
+
+  // endregion MaxSAT
+
+  // region Model enumeration
+
+  /// Finds up to `max` distinct models. After each model is found, a blocking clause is added, namely
+  /// the negation of the model restricted to the decision variables, so that the next model differs.
+  /// Blocking clauses persist across calls until `reset`, so a later call only finds new models.
+  pub fn enumerate_models(&mut self, max: usize) -> Vec<Model> {
+    let mut models = Vec::new();
+
+    while models.len() < max {
+      let assumptions: LiteralVector = self.blocking_selector
+                                           .iter()
+                                           .map(|&selector| Literal::new(selector, false))
+                                           .collect();
+      if self.check(&assumptions) != LiftedBool::True {
+        break;
+      }
+      let model = self.model.clone();
+
+      // Every blocking clause contains the negated selector, so that `reset` can retract them all
+      // by fixing the selector false. The selector itself is not a decision variable.
+      let selector = match self.blocking_selector {
+        Some(selector) => selector,
+        None           => {
+          let selector = self.mk_var(false, false);
+          self.blocking_selector = Some(selector);
+          selector
+        }
+      };
+      let mut blocking_clause = vec![Literal::new(selector, true)];
+      for v in 0..self.number_of_variables() as BoolVariable {
+        if self.decision[v] && model[v] != LiftedBool::Undefined {
+          blocking_clause.push(Literal::new(v, model[v] == LiftedBool::True));
+        }
+      }
+      self.mk_clause_core(&blocking_clause, Status::input());
+
+      models.push(model);
+    }
+
+    models
+  }
+
+  /// Retracts the blocking clauses added by `enumerate_models` and resets the glue averages, which
+  /// otherwise carry over from previous searches.
+  pub fn reset(&mut self) {
+    if let Some(selector) = self.blocking_selector.take() {
+      // Satisfies every blocking clause. `simplify` removes them.
+      self.mk_clause_core(&vec![Literal::new(selector, true)], Status::input());
+    }
+
+    self.fast_glue_avg.reset();
+    self.slow_glue_avg.reset();
+    self.fast_glue_backup.reset();
+    self.slow_glue_backup.reset();
+  }
+
+  // endregion Model enumeration
+
+  fn simplify_clause(&self, literals: &mut LiteralVector) -> bool {
+        if self.at_base_level(){
+          return self.simplify_clause_core::<true>(literals);
+        }
+        else {
+          return self.simplify_clause_core::<false>(literals);
+        }
+    }
+
+}
+
+
+/// Verifies the two-watched-literal invariants. The watch list of a literal `l` holds the watches
+/// of clauses containing `!l`, so for every watch in every list we check that:
+///
+///  1. the watched literal is one of the clause's first two literals, and
+///  2. if the watched literal is false, the clause is satisfied.
+///
+/// We also check that every clause that is watched at all is watched exactly twice. The second
+/// property only holds at a propagation fixpoint.
+///
+/// The checks only read the watch lists, so they run on hand-built lists as well as the solver's.
+#[cfg(debug_assertions)]
+fn check_watch_lists<'c>(
+  watches  : &[WatchList],
+  value    : impl Fn(Literal) -> LiftedBool,
+  clause_at: impl Fn(ClauseOffset) -> &'c Clause
+) -> Result<(), String>
+{
+  let mut watch_counts: HashMap<ClauseOffset, u32> = HashMap::new();
+
+  for (index, watch_list) in watches.iter().enumerate() {
+    let watched_literal = !Literal(index);
+    let is_false        = value(watched_literal) == LiftedBool::False;
+
+    for watched in watch_list.list.iter() {
+      match *watched {
+
+        Watched::Binary { literal, .. } => {
+          if is_false && value(literal) != LiftedBool::True {
+            return Err(format!("binary clause ({} {}) is not propagated", watched_literal, literal));
+          }
+        }
+
+        Watched::Ternary(literal1, literal2) => {
+          let value1 = value(literal1);
+          let value2 = value(literal2);
+          if is_false
+              && value1 != LiftedBool::True
+              && value2 != LiftedBool::True
+              && (value1 == LiftedBool::False || value2 == LiftedBool::False)
+          {
+            return Err(
+              format!("ternary clause ({} {} {}) is not propagated", watched_literal, literal1, literal2)
+            );
+          }
+        }
+
+        Watched::Clause { clause_offset, .. } => {
+          let clause = clause_at(clause_offset);
+          *watch_counts.entry(clause_offset).or_insert(0) += 1;
+
+          if clause.size() < 2 || (clause[0usize] != watched_literal && clause[1usize] != watched_literal) {
+            return Err(format!("clause {} is in the watch list of {} but does not watch it", clause, !watched_literal));
+          }
+          let is_satisfied = clause.literals()
+                                   .iter()
+                                   .any(|&literal| value(literal) == LiftedBool::True);
+          if is_false && !is_satisfied {
+            return Err(format!("clause {} watches false literal {} but is not satisfied", clause, watched_literal));
+          }
+        }
+
+        Watched::ExtensionConstraint(_) => { /* Checked by the extension. */ }
+
+      }
+    }
+  }
+
+  for (offset, count) in watch_counts {
+    if count != 2 {
+      return Err(format!("clause {} is watched {} times", clause_at(offset), count));
+    }
+  }
+
+  Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{parameters::ParameterValue, Statistic};
+
+  #[test]
+  #[cfg(debug_assertions)]
+  fn watch_invariant_checker_reports_corruption() {
+    let x = Literal::new(0, false);
+    let y = Literal::new(1, false);
+    let z = Literal::new(2, false);
+
+    // The binary clause `x ∨ y` with `x` false and `y` true.
+    let mut watches = vec![WatchList { list: Vec::new() }; 6];
+    watches[(!x).index()].list.push(Watched::Binary { literal: y, is_learned: false });
+    watches[(!y).index()].list.push(Watched::Binary { literal: x, is_learned: false });
+    let value = |literal: Literal| {
+      match literal {
+        l if l == y || l == !x => LiftedBool::True,
+        l if l == x || l == !y => LiftedBool::False,
+        _                      => LiftedBool::Undefined,
+      }
+    };
+    let no_clauses = |_: ClauseOffset| -> &'static Clause { unreachable!() };
+    assert!(check_watch_lists(&watches, value, no_clauses).is_ok());
+
+    // Point the watch of `x` at the unassigned `z`, so that the false `x` is left unpropagated.
+    watches[(!x).index()].list[0] = Watched::Binary { literal: z, is_learned: false };
+    assert!(check_watch_lists(&watches, value, no_clauses).is_err());
+  }
+
+  #[test]
+  fn maxsat_finds_minimum_cost() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+
+    // Hard: at most one of `a`, `b`.
+    solver.mk_clause_core(&vec![!a, !b], Status::input());
+    // Soft: `a` with weight 3, `b` with weight 2.
+    solver.add_soft_clause(&vec![a], 3);
+    solver.add_soft_clause(&vec![b], 2);
+
+    let (cost, model) = solver.solve_maxsat();
+    assert_eq!(cost, 2);
+    assert_eq!(model.unwrap()[a.var()], LiftedBool::True);
+  }
+
+  #[test]
+  fn totalizer_bounds_cost() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+
+    let ra = solver.add_soft_clause(&vec![a], 3);
+    let rb = solver.add_soft_clause(&vec![b], 2);
+    // Violate the first soft clause, so the second one fits under a cost of 5 but not of 3.
+    solver.mk_clause_core(&vec![Literal::new(ra, false)], Status::input());
+
+    let guard   = Literal::new(solver.mk_var(false, false), false);
+    let outputs = solver.mk_totalizer(guard, 5);
+    assert_eq!(outputs.iter().map(|&(sum, _)| sum).collect::<Vec<u64>>(), vec![2, 3, 5]);
+
+    solver.mk_clause_core(&vec![!guard, !outputs[2].1], Status::input());
+    assert_eq!(solver.check(&vec![guard]), LiftedBool::True);
+    assert_eq!(solver.model[rb], LiftedBool::False);
+    assert_eq!(solver.model[b.var()], LiftedBool::True);
+
+    solver.mk_clause_core(&vec![!guard, !outputs[1].1], Status::input());
+    assert_eq!(solver.check(&vec![guard]), LiftedBool::False);
+    // Without the guard, the totalizer constrains nothing.
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+  }
+
+  #[test]
+  fn models_after_maxsat_ignore_totalizer() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!a, !b], Status::input());
+    solver.add_soft_clause(&vec![a], 3);
+    solver.add_soft_clause(&vec![b], 2);
+
+    let (cost, _) = solver.solve_maxsat();
+    assert_eq!(cost, 2);
+
+    // The models differ in `a`, `b`, and the relaxation variables only: one with both soft clauses
+    // violated, and two each with exactly one of `a` and `b` true.
+    assert_eq!(solver.enumerate_models(10).len(), 5);
+  }
+
+  #[test]
+  fn occurrence_lists_enumerate_clauses() {
+    let mut solver = Solver::default();
+    let literals: LiteralVector = (0..5).map(|_| Literal::new(solver.mk_var(true, true), false))
+                                        .collect();
+
+    solver.mk_clause_core(&vec![literals[0], literals[1], literals[2], literals[3]], Status::input());
+    solver.mk_clause_core(&vec![literals[0], !literals[1], literals[3], literals[4]], Status::input());
+    solver.mk_clause_core(&vec![!literals[0], literals[2], literals[3], literals[4]], Status::input());
+
+    solver.build_occurrence_lists();
+    assert!(solver.occurrence_lists_valid());
+    assert_eq!(solver.occurrences(literals[0]).len(), 2);
+    assert_eq!(solver.occurrences(!literals[0]).len(), 1);
+    assert_eq!(solver.occurrences(literals[3]).len(), 3);
+    assert_eq!(solver.occurrences(!literals[4]).len(), 0);
+    for &offset in solver.occurrences(literals[1]) {
+      assert!(solver.get_clause(offset).contains_literal(literals[1]));
+    }
+
+    // Adding a clause invalidates the lists.
+    solver.mk_clause_core(&vec![literals[1], literals[2], literals[3], literals[4]], Status::input());
+    assert!(!solver.occurrence_lists_valid());
+  }
+
+  #[test]
+  fn learned_callback_observes_lemmas() {
+    use std::cell::RefCell;
+
+    let lemmas: Rc<RefCell<Vec<LiteralVector>>> = Rc::new(RefCell::new(Vec::new()));
+    let observed = lemmas.clone();
+
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    solver.set_learned_callback(Box::new(move |lemma| observed.borrow_mut().push(lemma.clone())));
+
+    for clause in [vec![a, b], vec![a, !b], vec![!a, b], vec![!a, !b]].iter() {
+      solver.mk_clause_core(clause, Status::input());
+    }
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert!(!lemmas.borrow().is_empty());
+    for lemma in lemmas.borrow().iter() {
+      assert!(!lemma.is_empty());
+      assert!(lemma.iter().all(|literal| literal.var() == a.var() || literal.var() == b.var()));
+    }
+  }
+
+  /// Adds the clauses stating that `pigeons` pigeons sit in `holes` holes, no two in the same hole.
+  /// Unsatisfiable when `pigeons > holes`, and hard to refute for resolution.
+  fn add_pigeonhole(solver: &mut Solver, pigeons: usize, holes: usize) {
+    let mut p: Vec<LiteralVector> = Vec::new();
+    for _ in 0..pigeons {
+      p.push((0..holes).map(|_| Literal::new(solver.mk_var(true, true), false)).collect());
+    }
+    for i in 0..pigeons {
+      solver.mk_clause_core(&p[i], Status::input());
+    }
+    for h in 0..holes {
+      for i in 0..pigeons {
+        for j in (i + 1)..pigeons {
+          solver.mk_clause_core(&vec![!p[i][h], !p[j][h]], Status::input());
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn tiny_max_memory_stops_learning() {
+    let mut solver = Solver::default();
+    solver.config.max_memory = 0;
+
+    // Refuting the pigeonhole principle requires learning.
+    add_pigeonhole(&mut solver, 4, 3);
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::Undefined);
+    assert!(solver.is_memory_exhausted());
+    assert_eq!(solver.reason_unknown(), "max. memory exceeded");
+  }
+
+  #[test]
+  fn gzipped_dimacs_round_trip() {
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+
+    let cnf = "c A small satisfiable problem\np cnf 3 3\n1 -2 3 0\n-1 2 0\n2 3 -1 0\n";
+    let path = std::env::temp_dir().join(format!("zsat-round-trip-{}.cnf.gz", std::process::id()));
+    let mut encoder = GzEncoder::new(std::fs::File::create(&path).unwrap(), Compression::default());
+    encoder.write_all(cnf.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+
+    let mut solver = Solver::from_dimacs_path(
+      &path,
+      std::sync::Arc::new(std::sync::RwLock::new(ResourceLimit::new()))
+    ).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(solver.number_of_variables(), 3);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    let (_, clauses) = parse_dimacs_cnf(cnf).unwrap();
+    for clause in clauses.iter() {
+      assert!(clause.literals().iter().any(|&literal| solver.model[literal.var()] == LiftedBool::from(!literal.sign())));
+    }
+  }
+
+  #[test]
+  fn icnf_queries_are_answered_in_order() {
+    let icnf = "p inccnf\n1 2 0\n-1 0\na 2 0\n-2 3\n0\na 2 -3 0\n";
+
+    let mut solver = Solver::default();
+    let results    = solver.run_icnf(icnf.as_bytes()).unwrap();
+
+    assert_eq!(results, vec![LiftedBool::True, LiftedBool::False]);
+    assert_eq!(solver.number_of_variables(), 3);
+    assert!(solver.run_icnf("a 1 -2\n".as_bytes()).is_err());
+  }
+
+  #[test]
+  fn annotated_dimacs_marks_learned_clauses() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 5, 4);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+
+    let mut plain = Vec::new();
+    solver.write_dimacs(&mut plain).unwrap();
+    let plain = String::from_utf8(plain).unwrap();
+    assert!(plain.starts_with("p cnf 20 "));
+    assert!(!plain.contains("c learned"));
+
+    let mut annotated = Vec::new();
+    solver.write_dimacs_annotated(&mut annotated).unwrap();
+    let annotated = String::from_utf8(annotated).unwrap();
+    assert!(annotated.contains("c learned\n"));
+    // Every learned clause follows its annotation.
+    let lines: Vec<&str> = annotated.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+      if *line == "c learned" {
+        assert!(lines[i + 1].ends_with(" 0"));
+      }
+    }
+  }
+
+  #[test]
+  fn verify_model_accepts_solution() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0], x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], x[1]], Status::input());
+    solver.mk_clause_core(&vec![x[2]], Status::input());
+
+    let mut model = Model::default();
+    for value in [LiftedBool::True, LiftedBool::True, LiftedBool::True] {
+      model.push(value);
+    }
+    assert_eq!(solver.verify_model(&model), Ok(()));
+  }
+
+  #[test]
+  fn verify_model_reports_violated_clause() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0], x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], x[1]], Status::input());
+
+    let mut model = Model::default();
+    for value in [LiftedBool::True, LiftedBool::False, LiftedBool::Undefined] {
+      model.push(value);
+    }
+    let mut violated = solver.verify_model(&model).unwrap_err();
+    violated.sort();
+    assert_eq!(violated, vec![!x[0], x[1]]);
+  }
+
+  #[test]
+  fn serialization_round_trip() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 4, 3);
+    let extra = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![extra], Status::input());
+
+    let mut bytes = Vec::new();
+    solver.serialize(&mut bytes).unwrap();
+    let mut copy = Solver::deserialize(
+      bytes.as_slice(),
+      std::sync::Arc::new(std::sync::RwLock::new(ResourceLimit::new()))
+    ).unwrap();
+
+    assert_eq!(copy.number_of_variables(), solver.number_of_variables());
+    assert_eq!(copy.number_of_clauses(), solver.number_of_clauses());
+    assert!(copy.is_fixed(extra));
+    assert_eq!(copy.check(&LiteralVector::new()), LiftedBool::False);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+
+    assert!(Solver::deserialize(&bytes[..bytes.len() - 1], solver.resource_limit()).is_err());
+  }
+
+  #[test]
+  fn preprocessing_shrinks_redundant_instance() {
+    let clause_count = |solver: &Solver| {
+      let mut dimacs = Vec::new();
+      solver.write_dimacs(&mut dimacs).unwrap();
+      let dimacs = String::from_utf8(dimacs).unwrap();
+      dimacs.lines().next().unwrap().split_whitespace().last().unwrap().parse::<usize>().unwrap()
+    };
+
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..5).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0], x[1]], Status::input());
+    solver.mk_clause_core(&vec![x[0], x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![x[0], !x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![x[0], !x[2], x[3], x[4]], Status::input());
+    solver.mk_clause_core(&vec![!x[1], x[2], !x[3], x[4]], Status::input());
+
+    let before = clause_count(&solver);
+    solver.preprocess(1000).unwrap();
+    assert_eq!(clause_count(&solver), before - 2);
+    assert!(solver.resource_limit().read().unwrap().not_canceled());
+  }
+
+  #[test]
+  fn learned_binary_does_not_subsume_exported_clause() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0], x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![x[0], x[1]], Status::redundant());
+
+    solver.preprocess(1000).unwrap();
+
+    // The learned binary is not written, so the clause it subsumes must be.
+    let mut dimacs = Vec::new();
+    solver.write_dimacs(&mut dimacs).unwrap();
+    assert!(String::from_utf8(dimacs).unwrap().starts_with("p cnf 3 1\n"));
+  }
+
+  #[test]
+  fn statistics_survive_cancelled_check() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 8, 7);
+    solver.resource_limit.write().unwrap().push(500);
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::Undefined);
+    assert_eq!(solver.last_status(), LiftedBool::Undefined);
+
+    let mut statistics = Statistics::new();
+    solver.collect_statistics(&mut statistics);
+    assert_ne!(statistics["sat conflicts"], Statistic::Integer(0));
+    assert_ne!(statistics["sat decisions"], Statistic::Integer(0));
+    assert!(solver.statistics_report().contains(":sat-conflicts"));
+  }
+
+  #[test]
+  fn progress_callback_reports_during_solve() {
+    use std::cell::Cell;
+
+    let reports  = Rc::new(Cell::new(0u32));
+    let observed = reports.clone();
+
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 6, 5);
+    solver.set_progress_callback(
+      10,
+      Box::new(move |statistics| {
+        assert_ne!(statistics["sat conflicts"], Statistic::Integer(0));
+        observed.set(observed.get() + 1);
+      })
+    );
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert!(reports.get() >= 1);
+  }
+
+  #[test]
+  fn size_histogram_counts_clauses_by_arity() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..5).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+
+    solver.mk_clause_core(&vec![x[0], x[1]], Status::input());
+    solver.mk_clause_core(&vec![!x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![x[0], !x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![x[0], x[1], x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], x[2], !x[3], x[4]], Status::input());
+
+    assert_eq!(solver.size_histogram(), vec![0, 0, 2, 1, 2]);
+    assert_eq!(solver.clauses_of_size(4).count(), 2);
+    assert!(solver.clauses_of_size(4).all(|clause| clause.size() == 4));
+    assert_eq!(solver.clauses_of_size(3).count(), 1);
+    assert_eq!(solver.clauses_of_size(2).count(), 0);
+  }
+
+  #[test]
+  fn conflicting_dimacs_assumptions_give_dimacs_core() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![x, y], Status::input());
+
+    let result = solver.check_dimacs_assumptions(&[1, -1]);
+    assert_eq!(result.status(), LiftedBool::False);
+    let mut core = result.dimacs_core().unwrap();
+    core.sort();
+    assert_eq!(core, vec![-1, 1]);
+
+    assert_eq!(solver.check_dimacs_assumptions(&[-1]).status(), LiftedBool::True);
+  }
+
+  #[test]
+  fn cardinality_resolution_on_pivot() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..4).map(|v| Literal::new(v, false)).collect();
+
+    // x0 + x1 + x2 <= 1
+    let mut c1 = Constraint::new(1, 0);
+    for &literal in &[x[0], x[1], x[2]] {
+      c1.push(literal);
+    }
+    // !x0 + x3 <= 1
+    let mut c2 = Constraint::new(1, 1);
+    for &literal in &[!x[0], x[3]] {
+      c2.push(literal);
+    }
+
+    let resolvent = solver.resolve_cardinality(&c1, &c2, 0);
+    assert_eq!(resolvent.k, 1);
+    assert_eq!(resolvent.literals, vec![x[1], x[2], x[3]]);
+
+    // !x0 + x1 <= 1. The sum is 2 x1 + x2 <= 1, weakened to x1 + x2 <= 1.
+    let mut c3 = Constraint::new(1, 2);
+    for &literal in &[!x[0], x[1]] {
+      c3.push(literal);
+    }
+    let resolvent = solver.resolve_cardinality(&c1, &c3, 0);
+    assert_eq!(resolvent.k, 1);
+    assert_eq!(resolvent.literals, vec![x[1], x[2]]);
+  }
+
+  #[test]
+  fn simplify_removes_redundant_clauses() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..8).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+
+    // Kept
+    solver.mk_clause_core(&vec![x[0], x[1], x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![!x[3], !x[4], x[5], x[7]], Status::input());
+    // A duplicate
+    solver.mk_clause_core(&vec![x[0], x[1], x[2], x[3]], Status::input());
+    // Subsumed by the first clause
+    solver.mk_clause_core(&vec![x[0], x[1], x[2], x[3], x[4]], Status::input());
+    // Subsumed by the binary clause (x1 x4)
+    solver.mk_clause_core(&vec![x[1], x[4], !x[2], x[5]], Status::input());
+    solver.mk_clause_core(&vec![x[1], x[4]], Status::input());
+    // Satisfied by the unit x6
+    solver.mk_clause_core(&vec![x[6], !x[0], !x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![x[6]], Status::input());
+
+    assert_eq!(solver.clauses.len(), 6);
+    solver.simplify();
+    assert_eq!(solver.clauses.len(), 2);
+    assert_eq!(solver.m_simplifications, 1);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+  }
+
+  #[test]
+  fn take_model_returns_model_once() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!x, y], Status::input());
+    solver.mk_clause_core(&vec![x], Status::input());
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    assert!(solver.model_is_current());
+
+    let model = solver.take_model().unwrap();
+    assert_eq!(model[x.var()], LiftedBool::True);
+    assert_eq!(model[y.var()], LiftedBool::True);
+    assert!(!solver.model_is_current());
+    assert_eq!(solver.take_model(), None);
+  }
+
+  #[test]
+  fn solver_statistics_match_named_statistics() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 5, 4);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+
+    let conflicts = solver.solver_statistics().conflict;
+    assert!(conflicts > 0);
+
+    let mut statistics = Statistics::new();
+    solver.collect_statistics(&mut statistics);
+    assert_eq!(statistics["sat conflicts"], Statistic::from(conflicts));
+    assert_eq!(statistics["sat decisions"], Statistic::from(solver.solver_statistics().decision));
+  }
+
+  #[test]
+  fn copy_preserves_clauses() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..5).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0]], Status::input());
+    solver.mk_clause_core(&vec![x[1], !x[2]], Status::input());
+    solver.mk_clause_core(&vec![!x[1], x[3], x[4]], Status::input());
+    solver.mk_clause_core(&vec![x[1], x[2], !x[3], x[4]], Status::input());
+
+    let mut copy = Solver::default();
+    copy.copy(&solver, true);
+
+    assert_eq!(copy.number_of_variables(), solver.number_of_variables());
+    assert_eq!(copy.clauses.len(), solver.clauses.len());
+    assert_eq!(copy.size_histogram(), solver.size_histogram());
+    assert_eq!(copy.trail, solver.trail);
+  }
+
+  #[test]
+  fn default_solver_is_empty() {
+    let solver = Solver::default();
+    assert_eq!(solver.number_of_variables(), 0);
+    assert_eq!(solver.number_of_clauses(), 0);
+    assert!(solver.at_base_level());
+    assert!(!solver.inconsistent);
+  }
+
+  #[test]
+  fn from_params_limit_builds_usable_solver() {
+    let parameters     = Rc::new(RefCell::new(Parameters::default()));
+    let resource_limit = Arc::new(RwLock::new(ResourceLimit::new()));
+    let mut solver     = Solver::from_params_limit(parameters, resource_limit);
+
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![x, y], Status::input());
+    solver.mk_clause_core(&vec![!x], Status::input());
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    assert_eq!(solver.take_model().unwrap()[y.var()], LiftedBool::True);
+
+    // The parameters are honoured.
+    let parameters = Rc::new(RefCell::new(Parameters::default()));
+    parameters.borrow_mut().set_value("random_seed", ParameterValue::UnsignedInteger(7));
+    parameters.borrow_mut().set_value("restart", ParameterValue::Symbol("luby"));
+    let solver = Solver::from_params_limit(parameters, Arc::new(RwLock::new(ResourceLimit::new())));
+    assert_eq!(solver.config.random_seed, 7);
+    assert_eq!(solver.config.restart, RestartStrategy::Luby);
+  }
+
+  #[test]
+  fn enumerate_models_finds_each_model_once() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    // Exactly three models: every assignment except x = y = false.
+    solver.mk_clause_core(&vec![x, y], Status::input());
+
+    let models = solver.enumerate_models(10);
+    assert_eq!(models.len(), 3);
+    assert!(models.iter().all(|model| model[x.var()] == LiftedBool::True || model[y.var()] == LiftedBool::True));
+    for (i, model) in models.iter().enumerate() {
+      assert!(models[i + 1..].iter().all(|other| other[x.var()] != model[x.var()] || other[y.var()] != model[y.var()]));
+    }
+    assert!(solver.enumerate_models(1).is_empty());
+
+    solver.reset();
+    assert_eq!(solver.enumerate_models(10).len(), 3);
+  }
+
+  #[test]
+  fn model_from_assignment_reads_positive_literals() {
+    let mut solver = Solver::default();
+    let variables: Vec<BoolVariable> = (0..3).map(|_| solver.mk_var(true, true)).collect();
+
+    solver.assignment[Literal::positive(variables[0]).index()] = LiftedBool::True;
+    solver.assignment[Literal::negative(variables[0]).index()] = LiftedBool::False;
+    solver.assignment[Literal::positive(variables[1]).index()] = LiftedBool::False;
+    solver.assignment[Literal::negative(variables[1]).index()] = LiftedBool::True;
+    solver.assignment[Literal::positive(variables[2]).index()] = LiftedBool::True;
+    solver.assignment[Literal::negative(variables[2]).index()] = LiftedBool::False;
+    solver.eliminated[variables[2]] = true;
+
+    let model = solver.build_model_from_assignment();
+    assert_eq!(model[variables[0]], LiftedBool::True);
+    assert_eq!(model[variables[1]], LiftedBool::False);
+    assert_eq!(model[variables[2]], LiftedBool::Undefined);
+  }
+
+  #[test]
+  fn reordering_follows_changed_activities() {
+    let mut solver = Solver::default();
+    let variables: Vec<BoolVariable> = (0..4).map(|_| solver.mk_var(true, true)).collect();
+
+    solver.with_branching(
+      |branching, solver| branching.on_conflict(solver, &LiteralVector::new(), &[variables[2], variables[3]])
+    );
+    solver.with_branching(
+      |branching, solver| branching.on_conflict(solver, &LiteralVector::new(), &[variables[2]])
+    );
+    let reorder_lim = solver.reorder_lim;
+    solver.reorder_variables();
+
+    assert_eq!(solver.decide_literal().map(|l| l.var()), Some(variables[2]));
+    assert_eq!(solver.decide_literal().map(|l| l.var()), Some(variables[3]));
+    assert_eq!(solver.decide_literal().map(|l| l.var()), Some(variables[0]));
+    assert!(solver.reorder_lim >= reorder_lim);
+  }
+
+  /// Branches on the lowest unassigned variable, always positively, and counts its decisions.
+  struct LowestIndexFirst {
+    picks: Rc<RefCell<u32>>,
+  }
+
+  impl BranchingStrategy for LowestIndexFirst {
+    fn pick(&mut self, solver: &Solver) -> Option<Literal> {
+      let v = (0..solver.number_of_variables() as BoolVariable).find(
+        |&v| solver.is_decision(v) && solver.value(Literal::positive(v)) == LiftedBool::Undefined
+      )?;
+      *self.picks.borrow_mut() += 1;
+      Some(Literal::positive(v))
+    }
+  }
+
+  #[test]
+  fn custom_branching_strategy_drives_search() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    let z = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!x, !y], Status::input());
+    solver.mk_clause_core(&vec![x, y, z], Status::input());
+
+    let picks = Rc::new(RefCell::new(0));
+    solver.set_branching_strategy(Box::new(LowestIndexFirst { picks: picks.clone() }));
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    assert!(*picks.borrow() > 0);
+    // Deciding `x` first forces `y` false, and `z` is decided positively.
+    let model = solver.take_model().unwrap();
+    assert_eq!(model[x.var()], LiftedBool::True);
+    assert_eq!(model[y.var()], LiftedBool::False);
+    assert_eq!(model[z.var()], LiftedBool::True);
+  }
+
+  #[test]
+  fn empty_clause_is_unsatisfiable_without_search() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![x, !x], Status::input());
+    solver.mk_clause_core(&LiteralVector::new(), Status::input());
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert_eq!(solver.solver_statistics().decision, 0);
+    assert_eq!(solver.solver_statistics().conflict, 0);
+    assert!(solver.reason_unknown().is_empty());
+    assert!(solver.core.is_empty());
+  }
+
+  #[test]
+  fn contradictory_units_are_reported_in_core() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![x], Status::input());
+    solver.mk_clause_core(&vec![!x], Status::input());
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert_eq!(solver.solver_statistics().decision, 0);
+    assert!(solver.core.contains(&x));
+    assert!(solver.core.contains(&!x));
+  }
+
+  #[test]
+  fn conflict_found_by_search_leaves_core_empty() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    for &(a, b) in &[(x, y), (x, !y), (!x, y), (!x, !y)] {
+      solver.mk_clause_core(&vec![a, b], Status::input());
+    }
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert!(solver.core().is_empty());
+    // The refutation is remembered, and the next call agrees without searching.
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert!(solver.core().is_empty());
+  }
+
+  #[test]
+  fn propagated_units_are_fixed() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    let z = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![x], Status::input());
+    solver.mk_clause_core(&vec![!x, y], Status::input());
+    solver.mk_clause_core(&vec![y, z], Status::input());
+
+    assert!(solver.propagate().is_none());
+    assert!(solver.is_fixed(y));
+    assert!(solver.is_fixed(!y));
+    assert!(!solver.is_fixed(z));
+    assert_eq!(solver.fixed_literals(), vec![x, y]);
+  }
+
+  #[test]
+  fn restore_undoes_simplification() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0], x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![x[0], x[1], x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![x[1], x[3]], Status::input());
+
+    let snapshot     = solver.snapshot();
+    let clause_count = solver.clauses.len();
+    let histogram    = solver.size_histogram();
+
+    // Satisfies the first two clauses, and `simplify` eliminates them.
+    solver.mk_clause_core(&vec![x[0]], Status::input());
+    solver.simplify();
+    assert!(solver.clauses.len() < clause_count);
+
+    solver.restore(&snapshot);
+    assert_eq!(solver.clauses.len(), clause_count);
+    assert_eq!(solver.size_histogram(), histogram);
+    assert_eq!(solver.number_of_variables() as usize, snapshot.number_of_variables());
+    assert!(!solver.is_fixed(x[0]));
+  }
+
+  #[test]
+  fn variable_stats_count_eliminated_variables() {
+    let mut solver = Solver::default();
+    let variables: Vec<BoolVariable> = (0..5).map(|_| solver.mk_var(true, true)).collect();
+    assert_eq!(solver.variable_stats(), (5, 0, 0));
+
+    solver.eliminated[variables[3]] = true;
+    assert_eq!(solver.variable_stats(), (4, 1, 0));
+  }
+
+  #[test]
+  fn duplicate_clause_is_added_once() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    let clause    = vec![x[0], !x[1], x[2]];
+    let reordered = vec![x[2], x[0], !x[1]];
+
+    let before = solver.number_of_clauses();
+    assert!(solver.add_clause_checked(&clause, Status::input()));
+    assert!(solver.clause_exists(&reordered));
+    assert!(!solver.add_clause_checked(&reordered, Status::input()));
+    assert_eq!(solver.number_of_clauses(), before + 1);
+  }
+
+  #[test]
+  fn low_restart_threshold_restarts_early() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 5, 4);
+    // Unlike the default `RestartStrategy::Ema`, a static schedule restarts on the threshold alone.
+    solver.config.restart = RestartStrategy::Static;
+    solver.set_restart_threshold(1);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert!(solver.solver_statistics().restart > 0);
+  }
+
+  #[test]
+  fn phase_hints_pick_first_polarity() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    solver.set_phase_hint(x.var(), true);
+    solver.set_phase_hint(y.var(), false);
+    assert_eq!(solver.decide_literal(), Some(x));
+
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    let mut hints = Model::default();
+    hints.push(LiftedBool::False);
+    hints.push(LiftedBool::True);
+    solver.set_phase_hints_from_model(&hints);
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    let model = solver.take_model().unwrap();
+    assert_eq!(model[x.var()], LiftedBool::False);
+    assert_eq!(model[y.var()], LiftedBool::True);
+  }
+
+  #[test]
+  fn rephase_keeps_hints_off_the_best_trail() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    solver.set_phase_hint(y.var(), true);
+    assert_eq!(solver.rephase_lim, solver.config.rephase_base);
+
+    solver.scope_level = 1;
+    solver.assign(x, Justification::with_level(1));
+    solver.update_best_phase();
+    solver.do_rephase();
+
+    assert!(solver.phase[x.var()]);
+    assert!(solver.phase[y.var()]);
+  }
+
+  #[test]
+  fn explanation_lists_forcing_literals() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    let c = Literal::new(solver.mk_var(true, true), false);
+    let d = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!a, !b, c], Status::input());
+    solver.mk_clause_core(&vec![!c, d], Status::input());
+
+    // The assumptions are decisions, and the assignment stays in place after a satisfiable check.
+    assert_eq!(solver.check(&vec![a, b]), LiftedBool::True);
+
+    let mut explanation = solver.explain(c).unwrap();
+    explanation.sort();
+    assert_eq!(explanation, vec![a, b]);
+    assert_eq!(solver.explain(d), Some(vec![c]));
+    assert_eq!(solver.explain(a), Some(Vec::new()));
+    assert_eq!(solver.explain(!d), None);
+  }
+
+  #[test]
+  fn minimal_core_drops_unneeded_assumptions() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    let c = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!a, !b], Status::input());
+    solver.mk_clause_core(&vec![!b, c], Status::input());
+
+    assert_eq!(solver.check(&vec![a, b, c]), LiftedBool::False);
+    // Pretend conflict analysis blamed every assumption.
+    solver.core = vec![a, b, c];
+
+    let mut core = solver.minimal_core();
+    core.sort();
+    assert_eq!(core, vec![a, b]);
+    assert_eq!(solver.core().len(), 2);
+    assert_eq!(solver.minimal_core().len(), 2);
+  }
+
+  #[test]
+  fn variable_degrees_count_clauses() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..5).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0], x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], x[1]], Status::input());
+    solver.mk_clause_core(&vec![x[0], !x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![!x[1], x[3], !x[0], x[2]], Status::input());
+
+    assert_eq!(solver.variable_degrees(), vec![4, 3, 3, 2, 0]);
+    assert_eq!(solver.most_constrained_variable(), Some(x[0].var()));
+    assert_eq!(Solver::default().most_constrained_variable(), None);
+  }
+
+  #[test]
+  fn flips_are_counted_across_checks() {
+    let mut solver = Solver::default();
+    let x = Literal::new(solver.mk_var(true, true), false);
+    let y = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!x, y], Status::input());
+
+    for _ in 0..2 {
+      assert_eq!(solver.check(&vec![x]), LiftedBool::True);
+      assert_eq!(solver.check(&vec![!x, !y]), LiftedBool::True);
+    }
+    assert!(solver.flip_count(x.var()) >= 3);
+    assert!(solver.flip_count(y.var()) >= 3);
+
+    assert_eq!(solver.polarity_stats(), (0, 2));
+    assert_eq!(solver.check(&vec![x]), LiftedBool::True);
+    assert_eq!(solver.polarity_stats(), (2, 0));
+  }
+
+  #[test]
+  fn implications_are_counted_by_arity() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![!x[0], x[1]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], !x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], !x[1], !x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![x[0]], Status::input());
+
+    assert_eq!(solver.propagate(), None);
+    assert_eq!(solver.value(x[3]), LiftedBool::True);
+
+    let statistics = solver.solver_statistics();
+    assert_eq!(statistics.bin_propagate, 1);
+    assert_eq!(statistics.ter_propagate, 1);
+    assert_eq!(statistics.propagate, 1);
+  }
+
+  #[test]
+  fn binary_clauses_populate_implication_graph() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    let c = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!a, b], Status::input());
+    solver.mk_clause_core(&vec![!a, !b, c], Status::input());
+
+    assert_eq!(solver.binary_implications(a), &vec![b]);
+    assert_eq!(solver.binary_implications(!b), &vec![!a]);
+    assert!(solver.binary_implications(b).is_empty());
+    assert!(solver.binary_implications(c).is_empty());
+  }
+
+  #[test]
+  fn conflict_free_search_saves_target_phase() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![!x[0], x[1]], Status::input());
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    assert_eq!(solver.solver_statistics().conflict, 0);
+    assert_eq!(solver.best_phase_size, 3);
+
+    let model = solver.take_model().unwrap();
+    for literal in x.iter() {
+      assert_eq!(LiftedBool::from(solver.best_phase[literal.var()]), model[literal.var()]);
+    }
+  }
+
+  #[test]
+  fn partial_assignment_is_permanent() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!a, b], Status::input());
+    solver.mk_clause_core(&vec![!a, !b], Status::input());
+
+    assert!(matches!(solver.check_with_partial_assignment(&[!b]), CheckResult::Satisfiable(_)));
+    assert!(solver.is_fixed(!b));
+    assert!(matches!(solver.check_with_partial_assignment(&[a]), CheckResult::Unsatisfiable(_)));
+    // Unlike a failed assumption, the conflict persists.
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+  }
+
+  #[test]
+  fn low_glue_lemmas_survive_gc() {
+    let mut solver = Solver::default();
+    let literals: LiteralVector = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+
+    let lemma = |solver: &mut Solver, glue: u32| {
+      let offset = solver.cls_allocator.allocate(literals.clone(), true);
+      solver.cls_allocator.get_mut(offset).set_glue(glue);
+      solver.learned.push(offset);
+      solver.promote_if_low_glue(offset);
+    };
+    lemma(&mut solver, 10);
+    lemma(&mut solver, 2);
+
+    solver.gc();
+    assert_eq!(solver.learned.len(), 1);
+    let survivor = solver.get_clause(solver.learned[0]);
+    assert!(survivor.is_core());
+    assert_eq!(survivor.glue(), 2);
+  }
+
+  #[test]
+  fn fingerprint_ignores_clause_order() {
+    let make = |clauses: &[Vec<i32>]| {
+      let mut solver = Solver::default();
+      for _ in 0..4 {
+        solver.mk_var(true, true);
+      }
+      for clause in clauses.iter() {
+        let literals: LiteralVector = clause.iter().map(|&d| Literal::from_dimacs(d)).collect();
+        solver.mk_clause_core(&literals, Status::input());
+      }
+      solver.problem_fingerprint()
+    };
+
+    let forward  = make(&[vec![1, 2], vec![-2, 3, 4], vec![-1, -3, -4, 2]]);
+    let backward = make(&[vec![2, -4, -3, -1], vec![4, 3, -2], vec![2, 1]]);
+    let other    = make(&[vec![1, 2], vec![-2, 3, 4], vec![-1, -3, -4, -2]]);
+    assert_eq!(forward, backward);
+    assert_ne!(forward, other);
+
+    // A unit simplifies the clauses added after it, but not those added before.
+    assert_eq!(make(&[vec![1], vec![1, 2], vec![-1, 3]]), make(&[vec![-1, 3], vec![1, 2], vec![1]]));
+
+    // The algorithm is fixed, so the value is too.
+    assert_eq!(make(&[vec![-2, 1]]), 0x2221_fb62_6c71_7bbe);
+  }
+
+  #[test]
+  fn final_conflict_is_falsified() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    for clause in [vec![a, b], vec![a, !b], vec![!a, b], vec![!a, !b]] {
+      solver.mk_clause_core(&clause, Status::input());
+    }
+    assert_eq!(solver.final_conflict(), None);
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    let conflict = solver.final_conflict().expect("a base-level conflict");
+    assert!(!conflict.is_empty());
+    assert!(conflict.iter().all(|&literal| solver.value(literal) == LiftedBool::False));
+  }
+
+  #[test]
+  fn random_restarts_follow_conflicts() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 5, 4);
+    solver.config.random_restart_freq = 1.0;
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+
+    // The final conflict ends the search instead of restarting it.
+    let statistics = solver.solver_statistics();
+    assert!(statistics.conflict > 1);
+    assert!(statistics.restart + 1 >= statistics.conflict);
+  }
+
+  #[test]
+  fn derived_units_exclude_input_units() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!b], Status::input());
+    // Simplified to the unit `a` at the base level, since `b` is false.
+    solver.mk_clause_core(&vec![a, b], Status::input());
+
+    assert_eq!(solver.derived_units(), vec![a]);
+  }
+
+  #[test]
+  fn glue_threshold_follows_recent_glue() {
+    let survivors = |recent_glue: f64| {
+      let mut solver = Solver::default();
+      solver.config.gc_glue_factor = 1.0;
+      let literals: LiteralVector = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+      for glue in [5, 9] {
+        let offset = solver.cls_allocator.allocate(literals.clone(), true);
+        solver.cls_allocator.get_mut(offset).set_glue(glue);
+        solver.learned.push(offset);
+      }
+      solver.slow_glue_avg.update(recent_glue);
+      solver.gc();
+      solver.learned.len()
+    };
+
+    // Against low-glue conflicts the worse lemma goes as usual, but against high-glue conflicts it is
+    // typical and spared.
+    assert_eq!(survivors(4.0), 1);
+    assert_eq!(survivors(12.0), 2);
+  }
+
+  #[test]
+  fn observer_sees_every_decision_and_conflict() {
+    #[derive(Default)]
+    struct Counts {
+      decisions: u32,
+      conflicts: u32,
+    }
+    struct CountingObserver(Rc<RefCell<Counts>>);
+    impl SolverObserver for CountingObserver {
+      fn on_decision(&mut self, _literal: Literal, level: u32) {
+        assert!(level > 0);
+        self.0.borrow_mut().decisions += 1;
+      }
+      fn on_conflict(&mut self, _lemma: &LiteralVector) {
+        self.0.borrow_mut().conflicts += 1;
+      }
+    }
+
+    let counts     = Rc::new(RefCell::new(Counts::default()));
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 5, 4);
+    solver.set_observer(Box::new(CountingObserver(counts.clone())));
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+
+    let statistics = solver.solver_statistics();
+    assert!(statistics.conflict > 0);
+    assert_eq!(counts.borrow().decisions, statistics.decision);
+    assert_eq!(counts.borrow().conflicts, statistics.conflict);
+  }
+
+  #[test]
+  fn binary_minimization_resolves_lemma_literals() {
+    let minimize = |enabled: bool| {
+      let mut solver = Solver::default();
+      let x: LiteralVector = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+      solver.config.minimize_lemmas_binary = enabled;
+      solver.mk_clause_core(&vec![x[0], !x[1]], Status::input());
+
+      solver.m_lemma = vec![x[0], x[1], x[2]];
+      solver.minimize_lemma_binary();
+      (solver.m_lemma.clone(), solver.solver_statistics().minimized_lits, x)
+    };
+
+    let (lemma, minimized, x) = minimize(true);
+    assert_eq!(lemma, vec![x[0], x[2]]);
+    assert_eq!(minimized, 1);
+
+    let (lemma, minimized, x) = minimize(false);
+    assert_eq!(lemma, vec![x[0], x[1], x[2]]);
+    assert_eq!(minimized, 0);
+  }
+
+  #[test]
+  fn wcnf_splits_hard_and_soft_clauses() {
+    let input = "c weighted\np wcnf 3 4 10\n10 1 2 0\n10 -1 -3 0\n4 -2 0\n3 3 0\n";
+    let (solver, soft) = Solver::from_wcnf_reader(
+      input.as_bytes(),
+      std::sync::Arc::new(std::sync::RwLock::new(ResourceLimit::new()))
+    ).unwrap();
+
+    assert_eq!(solver.number_of_variables(), 3);
+    assert!(solver.clause_exists(&vec![Literal::from_dimacs(1), Literal::from_dimacs(2)]));
+    assert!(solver.clause_exists(&vec![Literal::from_dimacs(-1), Literal::from_dimacs(-3)]));
+    assert_eq!(
+      soft,
+      vec![(vec![Literal::from_dimacs(-2)], 4), (vec![Literal::from_dimacs(3)], 3)]
+    );
+  }
+
+  #[test]
+  fn tiebreak_seed_changes_only_decision_order() {
+    struct FirstDecision(Rc<RefCell<Option<Literal>>>);
+    impl SolverObserver for FirstDecision {
+      fn on_decision(&mut self, literal: Literal, _level: u32) {
+        self.0.borrow_mut().get_or_insert(literal);
+      }
+    }
+
+    let run = |seed: u32| {
+      let first      = Rc::new(RefCell::new(None));
+      let mut solver = Solver::default();
+      solver.set_tiebreak_seed(seed);
+      add_pigeonhole(&mut solver, 5, 4);
+      solver.set_observer(Box::new(FirstDecision(first.clone())));
+      let result = solver.check(&LiteralVector::new());
+      let first  = *first.borrow();
+      (result, first, solver.rand.data)
+    };
+
+    let (result1, first1, rand1) = run(1);
+    let (result2, first2, rand2) = run(2);
+    assert_eq!(result1, LiftedBool::False);
+    assert_eq!(result1, result2);
+    assert_ne!(first1, first2);
+    // The main generator is not drawn from for tie-breaking.
+    assert_eq!(rand1, rand2);
+  }
+
+  #[test]
+  fn integrity_check_reports_desynced_size() {
+    let mut solver = Solver::default();
+    let literals: LiteralVector = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    let offset = solver.cls_allocator.allocate(literals, true);
+    solver.learned.push(offset);
+    assert_eq!(solver.check_database_integrity(), Ok(()));
+
+    solver.cls_allocator.get_mut(offset).shrink(3);
+    assert!(solver.check_database_integrity().unwrap_err().contains("size 3"));
+  }
+
+  #[test]
+  fn no_gc_keeps_every_lemma() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 5, 4);
+    solver.config.no_gc = true;
+    solver.set_gc_threshold(1);
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert!(solver.solver_statistics().conflict > 1);
+    assert_eq!(solver.solver_statistics().gc_clause, 0);
+  }
+
+  #[test]
+  fn autarky_excludes_conflicting_phases() {
+    let mut solver = Solver::default();
+    let [a, b, c, d, e]: [Literal; 5] = std::array::from_fn(|_| Literal::new(solver.mk_var(true, true), false));
+    for clause in [vec![a, b], vec![a, c], vec![!c, d], vec![!d, c], vec![b, e], vec![!b, !e]] {
+      solver.mk_clause_core(&clause, Status::input());
+    }
+    for (literal, value) in [(a, true), (b, true), (c, false), (d, false), (e, true)] {
+      solver.set_phase_hint(literal.var(), value);
+    }
+
+    // The phases of `b` and `e` falsify `(!b !e)`, so they drop out, and the rest satisfy every
+    // clause they touch.
+    let autarky = solver.find_autarky().expect("an autarky");
+    assert_eq!(canonical_clause(&autarky), canonical_clause(&[a, !c, !d]));
+
+    // Above the base level, decisions could satisfy clauses the autarky would otherwise have to.
+    solver.push_scope();
+    solver.assign(a, Justification::with_level(1));
+    assert_eq!(solver.find_autarky(), None);
+  }
+
+  #[test]
+  fn external_model_hides_relaxation_variables() {
+    let mut solver = Solver::default();
+    let a          = Literal::new(solver.mk_var(true, true), false);
+    let relaxation = solver.add_soft_clause(&vec![!a], 1);
+    solver.mk_clause_core(&vec![a], Status::input());
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    let model = solver.get_external_model();
+    assert_eq!(model.get(a.var()), Some(LiftedBool::True));
+    assert_eq!(model.get(relaxation), Some(LiftedBool::Undefined));
+    assert_eq!(solver.model.get(relaxation), Some(LiftedBool::True));
+  }
+
+  #[test]
+  fn throughput_is_propagations_per_second() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 6, 5);
+    assert_eq!(solver.throughput(), 0.0);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+
+    let statistics   = solver.solver_statistics();
+    let propagations = statistics.propagate + statistics.bin_propagate + statistics.ter_propagate;
+    let expected     = propagations as f64 / solver.stopwatch.elapsed_seconds();
+    assert!(solver.throughput() > 0.0);
+    assert!((solver.throughput() - expected).abs() <= 1e-9 * expected);
+
+    // The rate depends on the wall clock, so only the report gives it.
+    let mut named = Statistics::new();
+    solver.collect_statistics(&mut named);
+    assert!(!named.contains_key("sat props/sec"));
+    assert!(solver.statistics_report().contains(":sat-props/sec "));
+  }
+
+  #[test]
+  fn assumption_stack_applies_to_every_check() {
+    let mut solver = Solver::default();
+    let a = Literal::new(solver.mk_var(true, true), false);
+    let b = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![!a, !b], Status::input());
+
+    solver.push_assumption(a);
+    solver.push_assumption(b);
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+
+    assert_eq!(solver.pop_assumption(), Some(b));
+    assert!(!solver.assumption_set.contains(b));
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    assert_eq!(solver.model.get(a.var()), Some(LiftedBool::True));
+  }
+
+  #[test]
+  fn new_solver_shares_resource_limit() {
+    let resource_limit = Arc::new(RwLock::new(ResourceLimit::new()));
+    let mut solver     = Solver::new(resource_limit.clone());
+    assert!(Arc::ptr_eq(&solver.resource_limit, &resource_limit));
+    assert_eq!(solver.number_of_variables(), 0);
+    assert_eq!(solver.number_of_clauses(), 0);
+
+    let a = Literal::new(solver.mk_var(true, true), false);
+    solver.mk_clause_core(&vec![a], Status::input());
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+  }
+
+  #[test]
+  fn occurrence_activities_decide_busiest_variable_first() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    // `x[3]` occurs in every clause.
+    for clause in [vec![x[0], x[3]], vec![x[1], !x[3]], vec![x[2], x[3], x[0]]] {
+      solver.mk_clause_core(&clause, Status::input());
+    }
+
+    solver.init_activity_from_occurrences();
+    assert_eq!(solver.decide_literal().map(|literal| literal.var()), Some(x[3].var()));
+  }
+
+  #[test]
+  fn mk_var_grows_per_variable_vectors_together() {
+    let mut solver = Solver::default();
+    for expected in 0..10 {
+      assert_eq!(solver.mk_var(expected % 2 == 0, true), expected);
+    }
+
+    assert_eq!(solver.number_of_variables(), 10);
+    assert_eq!(solver.solver_statistics().mk_var, 10);
+    assert_eq!(solver.watches.len(), 20);
+    assert_eq!(solver.assignment.len(), 20);
+    for length in [solver.decision.len(), solver.external.len(), solver.phase.len(),
+                   solver.last_value.len(), solver.flips.len(), solver.tiebreak.len()] {
+      assert_eq!(length, 10);
+    }
+    assert!(solver.external[4] && !solver.external[5]);
+  }
+
+  #[test]
+  fn propagate_forces_binary_implications() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![!x[0], x[1]], Status::input());
+    solver.mk_clause_core(&vec![!x[1], x[2]], Status::input());
+    solver.mk_clause_core(&vec![x[0]], Status::input());
+
+    assert_eq!(solver.propagate(), None);
+    assert_eq!(solver.value(x[2]), LiftedBool::True);
+    assert_eq!(solver.explain(x[2]), Some(vec![x[1]]));
+  }
+
+  #[test]
+  fn propagate_reports_falsified_clause() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![!x[0], !x[1], !x[2], x[3]], Status::input());
+    solver.mk_clause_core(&vec![!x[0], !x[1], !x[2], !x[3]], Status::input());
+    for &literal in x[..3].iter() {
+      solver.mk_clause_core(&vec![literal], Status::input());
+    }
+
+    let conflict = solver.propagate().expect("a conflict");
+    assert!(solver.inconsistent);
+    assert!(matches!(conflict.reason(), Reason::Clause(_)));
+    let clause = solver.final_conflict().expect("a falsified clause");
+    assert!(clause.iter().all(|&literal| solver.value(literal) == LiftedBool::False));
+  }
+
+  #[test]
+  fn analyze_conflict_learns_first_uip() {
+    let mut solver = Solver::default();
+    let [a, b, x, y, z, w]: [Literal; 6] =
+      std::array::from_fn(|_| Literal::new(solver.mk_var(true, true), false));
+    for clause in [vec![!a, x], vec![!b, y], vec![!y, !x, z], vec![!z, w], vec![!z, !w, !x]] {
+      solver.mk_clause_core(&clause, Status::input());
+    }
+
+    // Decide `a` at level 1 and `b` at level 2 by hand.
+    solver.scope_level = 1;
+    solver.assign(a, Justification::with_level(1));
+    assert_eq!(solver.propagate(), None);
+    solver.scope_level = 2;
+    solver.assign(b, Justification::with_level(2));
+    let conflict = solver.propagate().expect("a conflict at level 2");
+
+    // `z` dominates every path from `b` to the conflict, while `x` comes from level 1.
+    let (lemma, backjump_level) = solver.analyze_conflict(conflict);
+    assert_eq!(lemma, vec![!z, !x]);
+    assert_eq!(backjump_level, 1);
+  }
+
+  #[test]
+  fn lemma_minimization_can_be_disabled() {
+    let analyze = |minimize: bool| {
+      let mut solver = Solver::default();
+      let [a, b, c, x]: [Literal; 4] = std::array::from_fn(|_| Literal::new(solver.mk_var(true, true), false));
+      solver.config.minimize_lemmas        = minimize;
+      solver.config.minimize_lemmas_binary = false;
+      for clause in [vec![!a, x], vec![!b, c], vec![!c, !a, !x, !b]] {
+        solver.mk_clause_core(&clause, Status::input());
+      }
+
+      solver.scope_level = 1;
+      solver.assign(a, Justification::with_level(1));
+      assert_eq!(solver.propagate(), None);
+      solver.scope_level = 2;
+      solver.assign(b, Justification::with_level(2));
+      let conflict = solver.propagate().expect("a conflict at level 2");
+
+      let (mut lemma, _) = solver.analyze_conflict(conflict);
+      lemma.sort();
+      (lemma, [a, b, x])
+    };
+
+    // `!x` is implied by `!a`, which is also in the lemma.
+    let (lemma, [a, b, _]) = analyze(true);
+    assert_eq!(lemma, vec![!a, !b]);
+    let (lemma, [a, b, x]) = analyze(false);
+    assert_eq!(lemma, vec![!a, !b, !x]);
+  }
+
+  #[test]
+  fn check_finds_model_or_refutes() {
+    let mut solver = Solver::default();
+    let [a, b, c]: [Literal; 3] = std::array::from_fn(|_| Literal::new(solver.mk_var(true, true), false));
+    for clause in [vec![a, b], vec![!a, c], vec![!b, c]] {
+      solver.mk_clause_core(&clause, Status::input());
+    }
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    assert!(solver.model_is_current());
+    assert_eq!(solver.verify_model(&solver.model), Ok(()));
+
+    // `c` is implied, so assuming otherwise fails on that assumption alone.
+    assert_eq!(solver.check(&vec![!c]), LiftedBool::False);
+    assert_eq!(solver.core(), &vec![!c]);
+
+    // Unsatisfiable without assumptions: the core is empty, and stays so.
+    add_pigeonhole(&mut solver, 3, 2);
+    assert_eq!(solver.check(&vec![a]), LiftedBool::False);
+    assert!(solver.core().is_empty());
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+  }
+
+  #[test]
+  fn clause_count_ignores_decisions() {
+    let mut solver = Solver::default();
+    let x: Vec<Literal> = (0..3).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0]], Status::input());
+    solver.mk_clause_core(&vec![x[1], x[2]], Status::input());
+    assert_eq!(solver.number_of_clauses(), 2);
+
+    // A satisfiable check leaves its decisions on the trail.
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::True);
+    assert!(!solver.at_base_level());
+    assert_eq!(solver.number_of_clauses(), 2);
+  }
+
+  #[test]
+  fn imported_lemmas_are_simplified() {
+    let mut solver = Solver::default();
+    let x: LiteralVector = (0..4).map(|_| Literal::new(solver.mk_var(true, true), false)).collect();
+    solver.mk_clause_core(&vec![x[0]], Status::input());
+
+    solver.import_lemma(&vec![!x[0], x[1], x[2]]);
+    // Satisfied at the base level, so it is dropped.
+    solver.import_lemma(&vec![x[3], x[0], x[1]]);
+
+    let (_, learned) = solver.clause_database();
+    assert_eq!(learned, vec![vec![x[1], x[2]]]);
+  }
+
+  #[test]
+  fn restart_runs_simplification() {
+    let mut solver = Solver::default();
+    add_pigeonhole(&mut solver, 5, 4);
+    solver.config.restart = RestartStrategy::Static;
+    solver.set_restart_threshold(1);
+
+    assert_eq!(solver.check(&LiteralVector::new()), LiftedBool::False);
+    assert!(solver.solver_statistics().restart > 0);
+    assert!(solver.m_simplifications > 0);
+  }
 }
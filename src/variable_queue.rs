@@ -0,0 +1,169 @@
+/*!
+
+The `VariableQueue` is the case split queue: a binary max-heap of the variables available for
+decisions, ordered by activity. The activities themselves are owned by the branching heuristic and
+passed to every operation that compares variables, just as z3's `var_queue` holds a reference to the
+solver's activity vector.
+
+Ties are broken in favor of the smaller variable, which keeps decisions deterministic.
+
+*/
+
+use crate::BoolVariable;
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct VariableQueue {
+  heap     : Vec<BoolVariable>,
+  positions: Vec<Option<usize>>, // Position of each variable in `heap`, indexed by variable
+}
+
+impl VariableQueue {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Makes room for the variables `0..variable_count`.
+  pub fn reserve(&mut self, variable_count: usize) {
+    if self.positions.len() < variable_count {
+      self.positions.resize(variable_count, None);
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.heap.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.heap.is_empty()
+  }
+
+  pub fn contains(&self, v: BoolVariable) -> bool {
+    matches!(self.positions.get(v), Some(Some(_)))
+  }
+
+  pub fn clear(&mut self) {
+    for &v in self.heap.iter() {
+      self.positions[v] = None;
+    }
+    self.heap.clear();
+  }
+
+  /// Inserts `v` unless it is already queued.
+  pub fn insert(&mut self, v: BoolVariable, activity: &[u32]) {
+    if self.contains(v) {
+      return;
+    }
+    self.reserve(v + 1);
+    self.heap.push(v);
+    self.positions[v] = Some(self.heap.len() - 1);
+    self.sift_up(self.heap.len() - 1, activity);
+  }
+
+  /// The variable of highest activity, which stays queued.
+  pub fn peek_max(&self) -> Option<BoolVariable> {
+    self.heap.first().copied()
+  }
+
+  /// Removes and returns the variable of highest activity.
+  pub fn pop_max(&mut self, activity: &[u32]) -> Option<BoolVariable> {
+    if self.heap.is_empty() {
+      return None;
+    }
+
+    let max  = self.heap.swap_remove(0);
+    self.positions[max] = None;
+    if let Some(&first) = self.heap.first() {
+      self.positions[first] = Some(0);
+      self.sift_down(0, activity);
+    }
+    Some(max)
+  }
+
+  /// Restores the heap order after the activity of `v` was increased.
+  pub fn activity_increased(&mut self, v: BoolVariable, activity: &[u32]) {
+    if let Some(Some(position)) = self.positions.get(v) {
+      self.sift_up(*position, activity);
+    }
+  }
+
+  /// Restores the heap order after the activity of `v` was decreased.
+  pub fn activity_decreased(&mut self, v: BoolVariable, activity: &[u32]) {
+    if let Some(Some(position)) = self.positions.get(v) {
+      self.sift_down(*position, activity);
+    }
+  }
+
+  /// Restores the heap order after arbitrary changes to the activities.
+  pub fn rebuild(&mut self, activity: &[u32]) {
+    for position in (0..self.heap.len() / 2).rev() {
+      self.sift_down(position, activity);
+    }
+  }
+
+  /// Whether the variable at heap position `a` should be above the one at position `b`.
+  fn precedes(&self, a: usize, b: usize, activity: &[u32]) -> bool {
+    let (u, v) = (self.heap[a], self.heap[b]);
+    activity[u] > activity[v] || (activity[u] == activity[v] && u < v)
+  }
+
+  fn swap(&mut self, a: usize, b: usize) {
+    self.heap.swap(a, b);
+    self.positions[self.heap[a]] = Some(a);
+    self.positions[self.heap[b]] = Some(b);
+  }
+
+  fn sift_up(&mut self, mut position: usize, activity: &[u32]) {
+    while position > 0 {
+      let parent = (position - 1) / 2;
+      if !self.precedes(position, parent, activity) {
+        break;
+      }
+      self.swap(position, parent);
+      position = parent;
+    }
+  }
+
+  fn sift_down(&mut self, mut position: usize, activity: &[u32]) {
+    loop {
+      let left  = 2 * position + 1;
+      let right = left + 1;
+      let mut best = position;
+
+      if left < self.heap.len() && self.precedes(left, best, activity) {
+        best = left;
+      }
+      if right < self.heap.len() && self.precedes(right, best, activity) {
+        best = right;
+      }
+      if best == position {
+        break;
+      }
+      self.swap(position, best);
+      position = best;
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn pops_by_activity_after_rebuild() {
+    let mut activity = vec![5, 1, 9, 1];
+    let mut queue    = VariableQueue::new();
+    for v in 0..activity.len() {
+      queue.insert(v, &activity);
+    }
+    assert_eq!(queue.pop_max(&activity), Some(2));
+
+    activity[1] = 20;
+    queue.rebuild(&activity);
+    assert_eq!(queue.pop_max(&activity), Some(1));
+    assert_eq!(queue.pop_max(&activity), Some(0));
+    assert_eq!(queue.pop_max(&activity), Some(3));
+    assert_eq!(queue.pop_max(&activity), None);
+    assert!(!queue.contains(2));
+  }
+}
@@ -72,7 +72,7 @@ impl Watched {
 
 /// A wrapper for `Vec<Watched>` that provides find and erase methods that compare without respect to `is_learned`
 /// or, for a `Watched::Clause`, its `literal`. The wrapped `Vec` is public to provide all the usual methods if needed.
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
 pub struct WatchList {
   pub list: Vec<Watched>
 }